@@ -0,0 +1,243 @@
+use cards::{Card, CardType, Color};
+use game_state::{Action, Pending, WILD_COLORS, active_color, get_deck};
+use mcts::{Budget, DEFAULT_ITERATIONS, MctsPlayer};
+use player::{OncePerTurn, Player, RandomPlayer};
+use player_view::PlayerView;
+use std::fmt;
+
+/// How well a built-in bot plays
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Plays a random legal action
+    Easy,
+    /// Attacks a player who is about to win, keeps its wild cards and calls UNO
+    Medium,
+    /// Plays like Medium, but also remembers which colors each player has drawn
+    /// instead of playing, and challenges a WildPlus4 that is likely a bluff
+    Hard,
+    /// Searches ahead with `MctsPlayer`. Much slower than the others
+    Expert,
+}
+
+/// The name of each difficulty, as used by `Difficulty::from_name`
+pub const DIFFICULTY_NAMES: [&str; 4] = ["easy", "medium", "hard", "expert"];
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            "expert" => Some(Difficulty::Expert),
+            _ => None,
+        }
+    }
+
+    /// Constructs a bot of this difficulty. Only the Easy and Expert bots make random choices,
+    /// which are determined by `seed`
+    pub fn bot(&self, seed: u64) -> Box<dyn Player> {
+        self.bot_with_budget(seed, Budget::iterations(DEFAULT_ITERATIONS))
+    }
+
+    /// Constructs a bot like `bot`, where an Expert bot searches within `budget` on each decision
+    pub fn bot_with_budget(&self, seed: u64, budget: Budget) -> Box<dyn Player> {
+        match *self {
+            Difficulty::Easy => Box::new(RandomPlayer::new(seed)),
+            Difficulty::Expert => Box::new(MctsPlayer::new(budget, seed)),
+            _ => Box::new(HeuristicPlayer::new(*self)),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+/// The Medium and Hard bots. Each legal action is given a score and the best one is taken
+pub struct HeuristicPlayer {
+    difficulty: Difficulty,
+    /// Whether UNO has been called this turn
+    uno_called: OncePerTurn,
+    /// The last view seen, to work out what changed since (Hard only)
+    last_view: Option<PlayerView>,
+    /// The colors each player is thought not to hold, because they drew instead of
+    /// playing onto them (Hard only)
+    lacks: Vec<Vec<Color>>,
+}
+
+impl HeuristicPlayer {
+    pub fn new(difficulty: Difficulty) -> HeuristicPlayer {
+        HeuristicPlayer {
+            difficulty: difficulty,
+            uno_called: OncePerTurn::new(),
+            last_view: None,
+            lacks: Vec::new(),
+        }
+    }
+
+    /// Returns true if the given player is thought not to hold any cards of `color`
+    pub fn lacks(&self, player: usize, color: Color) -> bool {
+        self.lacks.get(player).is_some_and(|colors| colors.contains(&color))
+    }
+
+    /// Returns the colors each player is thought not to hold
+    pub fn known_lacks(&self) -> &[Vec<Color>] {
+        &self.lacks
+    }
+
+    /// Updates what is known about the other players from what changed since the last view
+    pub fn observe(&mut self, view: &PlayerView) {
+        if self.difficulty != Difficulty::Hard {
+            return;
+        }
+        if self.lacks.len() != view.num_players() {
+            self.lacks = vec!(Vec::new(); view.num_players());
+        }
+        if let Some(ref last) = self.last_view {
+            let player: usize = last.current_player;
+            let played: bool = view.discard_pile.len() != last.discard_pile.len() || view.top_card != last.top_card;
+            // Drawing on their own turn either ends it or leaves them deciding on the drawn card,
+            // while being caught without calling UNO draws them cards without a turn passing
+            let drew: bool = view.turn != last.turn || matches!(view.pending, Some(Pending::DrawnCard(_)));
+            if !played && drew && last.pending.is_none() && view.hand_sizes[player] > last.hand_sizes[player] {
+                // They drew rather than play onto the active color
+                if !self.lacks[player].contains(&last.active_color) {
+                    self.lacks[player].push(last.active_color);
+                }
+            } else if played {
+                self.lacks[player].retain(|color| *color != view.active_color);
+                // Swapped or passed along hands take what was known with them
+                if view.rules.seven_zero && matches!(view.top_card.card_type, CardType::Number(7) | CardType::Number(0)) {
+                    self.lacks = vec!(Vec::new(); view.num_players());
+                }
+            }
+        }
+        self.last_view = Some(view.clone());
+    }
+
+    /// Returns an action that should be taken whether or not it is the bot's turn:
+    /// catching a player who did not call UNO, or calling UNO
+    pub fn urgent_action(&mut self, view: &PlayerView) -> Option<Action> {
+        if let Some(player) = view.uno_vulnerable {
+            if player != view.player && view.hand_sizes[player] == 1 {
+                return Some(Action::Catch(view.player, player));
+            }
+        }
+        let can_play: bool = view.legal_actions.iter().any(|action| matches!(*action, Action::Play(_, _)));
+        if view.is_my_turn() && view.hand.len() == 2 && can_play && self.uno_called.first_time(view) {
+            return Some(Action::CallUno);
+        }
+        None
+    }
+
+    /// Scores an action the bot may take on its turn. Higher is better
+    fn score(&self, view: &PlayerView, action: &Action) -> i32 {
+        let card: &Card = match *action {
+            Action::Play(ref card, _) => card,
+            Action::Pass => return -30,
+            _ => return -50,
+        };
+        let next: usize = view.next_player();
+        let color: Color = active_color(card);
+        // Get rid of cards worth the most points first, but keep wild cards for later
+        let mut score: i32 = match card.card_type {
+            CardType::Wild(_) | CardType::WildPlus4(_) if view.hand.len() > 2 => -40,
+            _ => card.points() as i32,
+        };
+        // Keep playing the color the bot holds the most of
+        score += 5 * view.hand.iter().filter(|x| x.color == color).count() as i32;
+        // Stop a player who is about to win
+        if view.hand_sizes[next] <= 2 {
+            match card.card_type {
+                CardType::Skip | CardType::Reverse | CardType::Plus2 | CardType::WildPlus4(_) => score += 100,
+                _ => {},
+            }
+        }
+        // Switch to a color the next player lacks once they are close to winning
+        if view.hand_sizes[next] <= 3 && self.lacks(next, color) {
+            score += 30;
+        }
+        score
+    }
+
+    /// Returns true if the WildPlus4 just played is likely a bluff, when it is worth challenging
+    fn should_challenge(&self, view: &PlayerView, offender: usize) -> bool {
+        if self.difficulty != Difficulty::Hard || view.discard_pile.len() < 2 {
+            return false;
+        }
+        let color: Color = active_color(&view.discard_pile[view.discard_pile.len() - 2]);
+        if self.lacks(offender, color) {
+            return false;
+        }
+        // The chance the offender held a card of the old color, from the cards the bot cannot see
+        let of_color = |card: &&Card| card.color == color;
+        let unseen_total: usize = get_deck().len() - view.discard_pile.len() - view.hand.len();
+        let unseen_color: usize = get_deck().iter().filter(of_color).count()
+            - view.discard_pile.iter().filter(of_color).count()
+            - view.hand.iter().filter(of_color).count();
+        if unseen_total == 0 {
+            return false;
+        }
+        let none_chance: f64 = (1.0 - unseen_color as f64 / unseen_total as f64).powi(view.hand_sizes[offender] as i32);
+        let bluff_chance: f64 = 1.0 - none_chance;
+        // A good challenge saves drawing 4 and gives the offender 4, a bad one draws 2 more
+        bluff_chance * 8.0 > none_chance * 2.0
+    }
+}
+
+impl Player for HeuristicPlayer {
+    fn choose_action(&mut self, view: &PlayerView) -> Action {
+        self.observe(view);
+        if let Some(action) = self.urgent_action(view) {
+            return action;
+        }
+        match view.pending {
+            Some(Pending::Challenge(offender)) => return Action::Challenge(self.should_challenge(view, offender)),
+            Some(Pending::SwapHands) => {
+                let fewest: usize = (0..view.num_players())
+                    .filter(|player| *player != view.player)
+                    .min_by_key(|player| view.hand_sizes[*player])
+                    .unwrap_or(view.player);
+                return Action::SwapHands(fewest);
+            },
+            _ => {},
+        }
+        let mut best: Option<(i32, Action)> = None;
+        for action in view.legal_actions.iter() {
+            let score: i32 = self.score(view, action);
+            match best {
+                Some((best_score, _)) if best_score >= score => {},
+                _ => best = Some((score, *action)),
+            }
+        }
+        best.expect("Expected a legal action on the player's turn").1
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        let next: usize = view.next_player();
+        let mut best: (usize, bool, Color) = (0, false, WILD_COLORS[0]);
+        for color in WILD_COLORS.iter() {
+            let held: usize = view.hand.iter().filter(|card| card.color == *color).count();
+            let candidate: (usize, bool, Color) = (held, self.lacks(next, *color), *color);
+            if (candidate.0, candidate.1) > (best.0, best.1) {
+                best = candidate;
+            }
+        }
+        best.2
+    }
+
+    fn react(&mut self, view: &PlayerView) -> Option<Action> {
+        self.observe(view);
+        if let Some(action) = self.urgent_action(view) {
+            return Some(action);
+        }
+        view.legal_actions.iter().cloned().find(|action| matches!(*action, Action::JumpIn(_, _)))
+    }
+}
@@ -40,10 +40,77 @@ impl Card {
              (Any, Wild(_)) | (Any, WildPlus4(_)) => return Ok(Card::new_from_any(color, card_type)),
              (_, Wild(_)) | (_, WildPlus4(_)) => return Err("Wild or WildPlus4 card must use Any color"),
              (Any, _) => return Err("Any color must only be used with Wild or WildPlus4 cardtype"),
-             (_, Number(x)) if (x < 0 ||  x > 9) => return Err("Number cardtype must have value between one and ten"),
+             (_, Number(x)) if !(0..=9).contains(&x) => return Err("Number cardtype must have value between one and ten"),
              (_, _) => return Ok(Card::new_from_any(color, card_type)),
          }
     }
+
+    /// Returns a short code for this card, as used in save files.
+    /// A colored card is its color's letter followed by its number, `skip`, `rev` or `+2`
+    /// (e.g. `r5`, `gskip`, `y+2`). Wild cards are `wild` or `wild+4`, followed by
+    /// a colon and the chosen color's letter if a color has been chosen (e.g. `wild+4:b`)
+    pub fn code(&self) -> String {
+        use cards::CardType::*;
+        let (name, chosen): (String, Color) = match self.card_type {
+            Number(x) => (format!("{}{}", self.color.code(), x), Color::Any),
+            Skip => (format!("{}skip", self.color.code()), Color::Any),
+            Reverse => (format!("{}rev", self.color.code()), Color::Any),
+            Plus2 => (format!("{}+2", self.color.code()), Color::Any),
+            Wild(x) => ("wild".to_owned(), x),
+            WildPlus4(x) => ("wild+4".to_owned(), x),
+        };
+        match chosen {
+            Color::Any => name,
+            _ => format!("{}:{}", name, chosen.code()),
+        }
+    }
+
+    /// Parses a code made by `Card::code`
+    pub fn from_code(code: &str) -> Result<Card, &'static str> {
+        use cards::CardType::*;
+        let (name, chosen): (&str, Color) = match code.find(':') {
+            Some(i) => match Color::from_code(&code[i + 1..]) {
+                Some(Color::Any) | None => return Err("Wild card has an invalid chosen color"),
+                Some(x) => (&code[..i], x),
+            },
+            None => (code, Color::Any),
+        };
+        match name {
+            "wild" => return Card::new_from(Color::Any, Wild(chosen)),
+            "wild+4" => return Card::new_from(Color::Any, WildPlus4(chosen)),
+            _ if chosen != Color::Any => return Err("Only wild cards may have a chosen color"),
+            _ => {},
+        }
+        if name.is_empty() || !name.is_char_boundary(1) {
+            return Err("Card code is not valid");
+        }
+        let color: Color = match Color::from_code(&name[..1]) {
+            Some(Color::Any) | None => return Err("Card code has an invalid color"),
+            Some(x) => x,
+        };
+        let card_type: CardType = match &name[1..] {
+            "skip" => Skip,
+            "rev" => Reverse,
+            "+2" => Plus2,
+            x => match x.parse::<i32>() {
+                Ok(n) => Number(n),
+                Err(_) => return Err("Card code has an invalid card type"),
+            },
+        };
+        Card::new_from(color, card_type)
+    }
+
+    /// The points this card is worth to the winner of a round when left in a loser's hand.
+    /// Numbers are worth their face value, Skip, Reverse and Plus2 are worth 20,
+    /// and wild cards are worth 50
+    pub fn points(&self) -> u32 {
+        use cards::CardType::*;
+        match self.card_type {
+            Number(x) => x as u32,
+            Reverse | Skip | Plus2 => 20,
+            Wild(_) | WildPlus4(_) => 50,
+        }
+    }
 }
 
 impl Iterator for Card {
@@ -101,6 +168,33 @@ pub enum Color {
     Any,
 }
 
+impl Color {
+    /// Returns the lowercase first letter of this color (`a` for Any)
+    pub fn code(&self) -> char {
+        use cards::Color::*;
+        match *self {
+            Red => 'r',
+            Green => 'g',
+            Yellow => 'y',
+            Blue => 'b',
+            Any => 'a',
+        }
+    }
+
+    /// Parses a code made by `Color::code`
+    pub fn from_code(code: &str) -> Option<Color> {
+        use cards::Color::*;
+        match code {
+            "r" => Some(Red),
+            "g" => Some(Green),
+            "y" => Some(Yellow),
+            "b" => Some(Blue),
+            "a" => Some(Any),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use cards::Color::*;
@@ -0,0 +1,199 @@
+use colorize;
+use external::{Connection, PROTOCOL_VERSION, VIEW_HEADER};
+use game_state::{Action, seat_label};
+use player::{Command, choose_command};
+use player_view::{PlayerView, SpectatorView};
+use read_stdin::{Input, StdinPlayer, parse_input, read_string_from_stdin};
+use save::{LoadError, SaveReader};
+use server::{SERVER_HEADER, SERVER_VERSION, SPECTATE_HEADER};
+use std::net::TcpStream;
+use std::time::Duration;
+
+pub const CONNECT_USAGE: &str = "Usage: uno connect <host:port> <name>";
+pub const WATCH_USAGE: &str = "Usage: uno watch <host:port> <name>";
+
+/// Runs `uno connect`, playing a match hosted by `uno serve` from this terminal.
+/// See the `server` module for the protocol
+pub fn run_client(args: &[String]) {
+    if args.len() != 2 {
+        eprintln!("{}", CONNECT_USAGE);
+        return;
+    }
+    let mut connection: Connection = match connect(&args[0], &format!("join {}", args[1])) {
+        Some(x) => x,
+        None => return,
+    };
+    println!("Joined the lobby. The match starts once it is full");
+
+    let mut view: Option<PlayerView> = None;
+    loop {
+        let line: String = match connection.receive() {
+            Ok(x) => x,
+            Err(_) => {
+                println!("Lost connection to the server");
+                return;
+            },
+        };
+        let (kind, rest): (&str, &str) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (&line, ""),
+        };
+        match kind {
+            "message" | "error" => println!("{}", rest),
+            "seat" => if let Ok(seat) = rest.parse::<usize>() {
+                println!("The match has started. You are player {}", seat_label(seat));
+            },
+            "go" => if let Some(ref view) = view {
+                colorize::print_player_view(view);
+                let action: Action = match read_action(view) {
+                    Some(x) => x,
+                    None => {
+                        connection.close();
+                        return;
+                    },
+                };
+                if connection.send(&action.code()).is_err() {
+                    println!("Lost connection to the server");
+                    return;
+                }
+            },
+            "react" => if let Some(ref view) = view {
+                colorize::print_player_view(view);
+                let reply: String = match read_reaction(view) {
+                    Some(Some(action)) => action.code(),
+                    Some(None) => "none".to_owned(),
+                    None => {
+                        connection.close();
+                        return;
+                    },
+                };
+                if connection.send(&reply).is_err() {
+                    println!("Lost connection to the server");
+                    return;
+                }
+            },
+            "quit" => return,
+            _ if kind == VIEW_HEADER => match read_block(&mut connection, &line, VIEW_HEADER, PROTOCOL_VERSION, PlayerView::read_view) {
+                Ok(x) => view = Some(x),
+                Err(err) => {
+                    println!("The server sent a view that could not be read: {}", err);
+                    return;
+                },
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Runs `uno watch`, showing the matches hosted by `uno serve` without playing in them
+pub fn run_spectator(args: &[String]) {
+    if args.len() != 2 {
+        eprintln!("{}", WATCH_USAGE);
+        return;
+    }
+    let mut connection: Connection = match connect(&args[0], &format!("watch {}", args[1])) {
+        Some(x) => x,
+        None => return,
+    };
+    loop {
+        let line: String = match connection.receive() {
+            Ok(x) => x,
+            Err(_) => {
+                println!("Lost connection to the server");
+                return;
+            },
+        };
+        if let Some(text) = line.strip_prefix("message ") {
+            println!("{}", text);
+        } else if line.starts_with(SPECTATE_HEADER) {
+            match read_block(&mut connection, &line, SPECTATE_HEADER, SERVER_VERSION, SpectatorView::read_view) {
+                Ok(view) => colorize::print_spectator_view(&view),
+                Err(err) => {
+                    println!("The server sent a view that could not be read: {}", err);
+                    return;
+                },
+            }
+        }
+    }
+}
+
+/// Connects to a server and sends the greeting. Prints why and returns None if that fails
+fn connect(address: &str, greeting: &str) -> Option<Connection> {
+    let stream: TcpStream = match TcpStream::connect(address) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("Could not connect to {}: {}", address, err);
+            return None;
+        },
+    };
+    let reader: TcpStream = stream.try_clone().expect("Expected to be able to read and write the connection");
+    // The server decides how long a turn may take
+    let mut connection: Connection = Connection::new(reader, stream, Duration::MAX);
+    match connection.receive() {
+        Ok(ref line) if line.starts_with(SERVER_HEADER) => {},
+        _ => {
+            eprintln!("{} is not an uno server", address);
+            return None;
+        },
+    }
+    if connection.send(greeting).is_err() {
+        eprintln!("Lost connection to the server");
+        return None;
+    }
+    Some(connection)
+}
+
+/// Reads the rest of a block that started with `first_line`, up to its `end` line,
+/// then reads its fields with `read`
+fn read_block<T>(connection: &mut Connection, first_line: &str, header: &str, version: u32,
+    read: fn(&mut SaveReader) -> Result<T, LoadError>) -> Result<T, LoadError> {
+    let mut text: String = format!("{}\n", first_line);
+    loop {
+        let line: String = connection.receive().map_err(|err| LoadError::Io(err.to_string()))?;
+        if line == "end" {
+            break;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    let mut reader: SaveReader = SaveReader::with_header(&text, header, version)?;
+    let value: T = read(&mut reader)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+/// Asks the person at the terminal for an action, filling in the color of any wild card.
+/// Returns None if they quit
+fn read_action(view: &PlayerView) -> Option<Action> {
+    loop {
+        match choose_command(&mut StdinPlayer, view) {
+            Command::Act(action) => return Some(action),
+            Command::Quit => return None,
+            _ => println!("Saving, loading, undo, history and scores are not available in online games"),
+        }
+    }
+}
+
+/// Asks the person at the terminal whether to jump in or catch someone out of turn.
+/// Returns None if they quit
+fn read_reaction(view: &PlayerView) -> Option<Option<Action>> {
+    let prompt: &str = if view.legal_actions.is_empty() {
+        "Someone can be caught for not calling UNO. Type \"catch\" and their number, or press Enter to let play go on"
+    } else {
+        "You can jump in with an identical card. Type it, or press Enter to let play go on"
+    };
+    loop {
+        let input: String = read_string_from_stdin(Some(prompt.to_owned()));
+        if input.trim().is_empty() {
+            return Some(None);
+        }
+        match parse_input(&input, view) {
+            // A card is played out of turn by jumping in with it
+            Input::Command(Command::Act(Action::Play(_, index))) => return Some(Some(Action::JumpIn(view.player, index))),
+            Input::Command(Command::Act(action)) => return Some(Some(action)),
+            Input::Command(Command::Quit) => return None,
+            Input::Invalid(message) => println!("{}", message),
+            _ => println!("Only a jump-in or a catch can be made right now"),
+        }
+    }
+}
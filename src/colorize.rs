@@ -2,25 +2,141 @@ extern crate term_painter as term;
 
 use self::term::ToStyle;
 
-use game_state::{GameState, playable_card};
-use cards::{Card, Color};
+use game_state::{GameState, Pending, TurnOrder, WILD_COLORS, playable_card, seat_label};
+use player_view::{PlayerView, SpectatorView};
+use cards::{Card, CardType, Color};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether cards are printed in color. Off leaves the output free of escape codes, for scripts
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Whether hands are printed sorted by color and number rather than in the order they were drawn
+static SORTED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(on: bool) {
+	COLOR.store(on, Ordering::Relaxed);
+}
+
+/// Turns sorting hands on or off. Returns whether it is now on
+pub fn toggle_sorted() -> bool {
+	!SORTED.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// A card ready to print, painted unless color is turned off
+pub enum CardText {
+	Painted(term::Painted<String>),
+	Plain(String),
+}
+
+impl fmt::Display for CardText {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			CardText::Painted(ref x) => write!(f, "{}", x),
+			CardText::Plain(ref x) => write!(f, "{}", x),
+		}
+	}
+}
 
 pub fn print_game_state(game_state: &GameState) {
-	let top_card = game_state.top_card();
-	println!("Top card is {}", color_card(top_card).paint(format!("{}", top_card)));
-	println!("Your turn player {}!", game_state.players.current_player + 1);
+	print_player_view(&game_state.view_for(game_state.players.current_player));
+}
+
+/// Prints what the viewer is allowed to see, including their own hand
+pub fn print_player_view(view: &PlayerView) {
+	let top_card = &view.top_card;
+	println!("Top card is {}", paint_card(top_card));
+	let hand_sizes: Vec<String> = view.hand_sizes.iter().enumerate()
+		.map(|(i, size)| format!("Player {}: {}", seat_label(i), size))
+		.collect();
+	println!("Cards in hand: {}", hand_sizes.join(", "));
+	println!("Your turn player {}!", seat_label(view.current_player));
+	match view.pending {
+		Some(Pending::DrawnCard(index)) => println!("You drew card [{}]. Play it or pass", index + 1),
+		Some(Pending::DrawPenalty(n)) => println!("Stack a draw card or pass to draw {}", n),
+		Some(Pending::SwapHands) => println!("Choose a player to swap hands with"),
+		Some(Pending::ChooseColor) => println!("Choose the color of the starting Wild"),
+		Some(Pending::Challenge(offender)) => println!("Player {} played a Wild Plus 4 onto you", seat_label(offender)),
+		None => {},
+	}
+	print_hand(view);
+}
+
+/// Prints the viewer's hand with the number of each card. Sorting only changes the order they are printed in
+pub fn print_hand(view: &PlayerView) {
 	println!("Your hand");
-	for (i, card) in game_state.players.get_current_player().iter().enumerate() {
-		println!("[{}]: {}", i + 1, underline_if_playable(card, top_card));
+	let mut order: Vec<usize> = (0..view.hand.len()).collect();
+	if SORTED.load(Ordering::Relaxed) {
+		order.sort_by_key(|i| sort_key(&view.hand[*i]));
+	}
+	for i in order {
+		println!("[{}]: {}", i + 1, underline_if_playable(&view.hand[i], &view.top_card));
 	}
 }
 
-pub fn underline_if_playable(card: &Card, onto: &Card) -> term::Painted<String> {
+/// Orders cards by color, wild cards last, then by number with action cards after the numbers
+fn sort_key(card: &Card) -> (usize, i32) {
+	let color: usize = WILD_COLORS.iter().position(|x| *x == card.color).unwrap_or(WILD_COLORS.len());
+	let rank: i32 = match card.card_type {
+		CardType::Number(x) => x,
+		CardType::Skip => 10,
+		CardType::Reverse => 11,
+		CardType::Plus2 => 12,
+		CardType::Wild(_) => 13,
+		CardType::WildPlus4(_) => 14,
+	};
+	(color, rank)
+}
+
+/// Prints what a spectator is allowed to see. Hands are only printed if the host shows them
+pub fn print_spectator_view(view: &SpectatorView) {
+	let top_card = &view.top_card;
+	println!("Top card is {}", paint_card(top_card));
+	let hand_sizes: Vec<String> = view.hand_sizes.iter().enumerate()
+		.map(|(i, size)| format!("Player {}: {}", seat_label(i), size))
+		.collect();
+	println!("Cards in hand: {}", hand_sizes.join(", "));
+	println!("Cards left to draw: {}. Play goes {}", view.draw_pile_count, match view.turn_order {
+		TurnOrder::Normal => "forward",
+		TurnOrder::Reverse => "backward",
+	});
+	println!("Player {}'s turn", seat_label(view.current_player));
+	match view.pending {
+		Some(Pending::DrawnCard(_)) => println!("They drew a card they can play"),
+		Some(Pending::DrawPenalty(n)) => println!("They must stack a draw card or draw {}", n),
+		Some(Pending::SwapHands) => println!("They are choosing a player to swap hands with"),
+		Some(Pending::ChooseColor) => println!("They are choosing the color of the starting Wild"),
+		Some(Pending::Challenge(offender)) => println!("Player {} played a Wild Plus 4 onto them", seat_label(offender)),
+		None => {},
+	}
+	if let Some(ref hands) = view.hands {
+		for (i, hand) in hands.iter().enumerate() {
+			// Painted cards write their colors to the terminal as they are printed, so each is printed on its own
+			print!("Player {}:", seat_label(i));
+			for card in hand.iter() {
+				print!(" {}", underline_if_playable(card, top_card));
+			}
+			println!();
+		}
+	}
+}
+
+pub fn underline_if_playable(card: &Card, onto: &Card) -> CardText {
+	if !COLOR.load(Ordering::Relaxed) {
+		return CardText::Plain(format!("{}", card));
+	}
 	if playable_card(card, onto) {
-		return color_card(card).underline().paint(format!("{}", card));
+		return CardText::Painted(color_card(card).underline().paint(format!("{}", card)));
 	} else {
-		return color_card(card).paint(format!("{}", card));
+		return CardText::Painted(color_card(card).paint(format!("{}", card)));
+	}
+}
+
+pub fn paint_card(card: &Card) -> CardText {
+	if !COLOR.load(Ordering::Relaxed) {
+		return CardText::Plain(format!("{}", card));
 	}
+	CardText::Painted(color_card(card).paint(format!("{}", card)))
 }
 
 pub fn color_card(card: &Card) -> term::Color {
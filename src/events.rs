@@ -0,0 +1,66 @@
+use cards::{Card, Color};
+use game_state::{TurnOrder, seat_label};
+use std::fmt;
+
+/// Something that happened in a game. GameEvents only hold public information,
+/// so drawn cards are counted but not shown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// The player played the card (Including any color chosen for a wild card)
+    CardPlayed(usize, Card),
+    /// The player drew this many cards
+    CardsDrawn(usize, usize),
+    /// The player's turn was skipped
+    TurnSkipped(usize),
+    /// The turn order is now this
+    DirectionReversed(TurnOrder),
+    /// The player chose this color for a wild card
+    ColorChosen(usize, Color),
+    /// The played cards under the top card were shuffled into the draw pile
+    DeckReshuffled,
+    /// The player kept a card they drew and ended their turn
+    Passed(usize),
+    /// The first player swapped hands with the second
+    HandsSwapped(usize, usize),
+    /// Every hand was passed to the next player in this turn order
+    HandsRotated(TurnOrder),
+    /// The player called UNO
+    UnoCalled(usize),
+    /// The player was caught not calling UNO
+    UnoCaught(usize),
+    /// The first player challenged the second player's WildPlus4, and succeeded if true
+    Challenged(usize, usize, bool),
+    /// The player played a card out of turn
+    JumpedIn(usize),
+    /// The player emptied their hand
+    PlayerWon(usize),
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::GameEvent::*;
+        match *self {
+            CardPlayed(player, card) => write!(f, "Player {} played {}", seat_label(player), card),
+            CardsDrawn(player, 1) => write!(f, "Player {} drew a card", seat_label(player)),
+            CardsDrawn(player, n) => write!(f, "Player {} drew {} cards", seat_label(player), n),
+            TurnSkipped(player) => write!(f, "Player {} was skipped", seat_label(player)),
+            DirectionReversed(TurnOrder::Normal) => write!(f, "Play goes forward"),
+            DirectionReversed(TurnOrder::Reverse) => write!(f, "Play goes backward"),
+            ColorChosen(player, color) => write!(f, "Player {} chose {}", seat_label(player), color),
+            DeckReshuffled => write!(f, "Refilled draw deck"),
+            Passed(player) => write!(f, "Player {} passed", seat_label(player)),
+            HandsSwapped(a, b) => write!(f, "Player {} swapped hands with player {}", seat_label(a), seat_label(b)),
+            HandsRotated(_) => write!(f, "Every hand was passed along"),
+            UnoCalled(player) => write!(f, "Player {} calls UNO!", seat_label(player)),
+            UnoCaught(player) => write!(f, "Player {} didn't call UNO!", seat_label(player)),
+            Challenged(challenger, offender, true) => {
+                write!(f, "Player {} caught player {} bluffing a Wild Plus 4", seat_label(challenger), seat_label(offender))
+            },
+            Challenged(challenger, offender, false) => {
+                write!(f, "Player {} wrongly challenged player {}'s Wild Plus 4", seat_label(challenger), seat_label(offender))
+            },
+            JumpedIn(player) => write!(f, "Player {} jumped in!", seat_label(player)),
+            PlayerWon(player) => write!(f, "Player {} won!", seat_label(player)),
+        }
+    }
+}
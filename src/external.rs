@@ -0,0 +1,338 @@
+//! External bots are programs that play a seat by reading and writing lines on their
+//! stdin and stdout, in the same spirit as the UCI protocol for chess engines,
+//! so they can be written in any language.
+//!
+//! ```text
+//! uno-bot 1                      host: sent once when the bot starts, with the protocol version
+//! ready My Bot                   bot: optionally followed by its name
+//! uno-view 1                     host: the bot's view of the game, followed by a request
+//! player 2                       the bot's seat. Players and card indices are 0-indexed
+//! hand r1 gskip b7               cards are written with `Card::code`
+//! hand-sizes 7 7 3 7             one per player, including the bot
+//! discard-pile r5 g5 g+2         top card last
+//! active-color g
+//! turn-order normal              normal or reverse
+//! current-player 2
+//! turn 14                        the number of turns started this round, including jump-ins
+//! draw-pile 80                   the number of cards left to draw
+//! pending none                   as in save files
+//! uno-vulnerable none            none or a player who did not call UNO and can be caught
+//! winner none
+//! rules stacking jump-in         the house rules that are on
+//! legal-actions 2
+//! legal play 1 gskip             one line per legal action, written with `Action::code`
+//! legal draw
+//! end
+//! go                             host: it is the bot's turn
+//! play 1 gskip                   bot: a legal action, `uno` or `catch` with its own seat and the one caught
+//! play 3 wild:g                  wild cards are always played with a color, so only a starting Wild asks for `color g`
+//! react                          host: sent out of turn when the bot could jump in or catch a player
+//! none                           bot: `none`, a legal jump-in or a catch
+//! quit                           host: sent when the game is over
+//! ```
+//!
+//! Each reply must arrive within the timeout. A bot that misses it, exits or closes its stdout
+//! is stopped and its seat is played by a Medium bot for the rest of the game. A reply that is
+//! not a legal action is replaced by the Medium bot's choice and the bot carries on.
+//! Anything the bot writes to stderr is shown after its name, along with the game's messages
+
+use bots::{Difficulty, HeuristicPlayer};
+use cards::Color;
+use game_state::{Action, Pending};
+use player::{OncePerTurn, Player};
+use player_view::PlayerView;
+use save::SaveWriter;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError, channel};
+use std::thread;
+use std::time::{Duration, Instant};
+use tui::Output;
+
+pub const PROTOCOL_HEADER: &str = "uno-bot";
+pub const VIEW_HEADER: &str = "uno-view";
+pub const PROTOCOL_VERSION: u32 = 1;
+/// How long a bot has to reply when no other timeout is given
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The reasons an external bot may stop playing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExternalError {
+    /// The program could not be started
+    Start(String),
+    /// The bot did not reply in time
+    Timeout,
+    /// The bot exited or closed its stdout
+    Exited,
+    /// The bot's first reply was not `ready`
+    NotReady(String),
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ExternalError::*;
+        match *self {
+            Start(ref message) => write!(f, "Could not start bot: {}", message),
+            Timeout => write!(f, "Bot did not reply in time"),
+            Exited => write!(f, "Bot exited"),
+            NotReady(ref reply) => write!(f, "Bot replied {:?} instead of \"ready\"", reply),
+        }
+    }
+}
+
+/// Sends and receives the lines of the protocol. Lines are received on their own thread
+/// so that waiting for a reply can time out
+pub struct Connection {
+    /// Taken when the connection is closed
+    writer: Option<Box<dyn Write + Send>>,
+    replies: Receiver<String>,
+    timeout: Duration,
+}
+
+impl Connection {
+    pub fn new<R: Read + Send + 'static, W: Write + Send + 'static>(reader: R, writer: W, timeout: Duration) -> Connection {
+        let (sender, replies) = channel::<String>();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let sent: bool = match line {
+                    Ok(line) => sender.send(line).is_ok(),
+                    Err(_) => false,
+                };
+                if !sent {
+                    break;
+                }
+            }
+        });
+        Connection {
+            writer: Some(Box::new(writer)),
+            replies: replies,
+            timeout: timeout,
+        }
+    }
+
+    /// Sets how long `receive` waits for a line
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends one line. The newline is added
+    pub fn send(&mut self, line: &str) -> Result<(), ExternalError> {
+        let writer: &mut Box<dyn Write + Send> = self.writer.as_mut().ok_or(ExternalError::Exited)?;
+        writer.write_all(format!("{}\n", line).as_bytes()).and_then(|_| writer.flush()).map_err(|_| ExternalError::Exited)
+    }
+
+    /// Sends the view's `uno-view` block followed by a request such as `go`
+    pub fn send_view(&mut self, view: &PlayerView, request: &str) -> Result<(), ExternalError> {
+        let mut writer: SaveWriter = SaveWriter::with_header(VIEW_HEADER, PROTOCOL_VERSION);
+        view.write_view(&mut writer);
+        self.send(&format!("{}end\n{}", writer.finish(), request))
+    }
+
+    /// Waits for the next line, without its newline
+    pub fn receive(&mut self) -> Result<String, ExternalError> {
+        match self.replies.recv_timeout(self.timeout) {
+            Ok(line) => Ok(line.trim().to_owned()),
+            Err(RecvTimeoutError::Timeout) => Err(ExternalError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(ExternalError::Exited),
+        }
+    }
+
+    /// Returns true if the other end has closed the connection. Lines waiting to be received are discarded
+    pub fn is_closed(&mut self) -> bool {
+        loop {
+            match self.replies.try_recv() {
+                Ok(_) => {},
+                Err(TryRecvError::Empty) => return self.writer.is_none(),
+                Err(TryRecvError::Disconnected) => return true,
+            }
+        }
+    }
+
+    /// Stops sending. The other end sees its input end
+    pub fn close(&mut self) {
+        self.writer = None;
+    }
+}
+
+/// A seat played by a bot program. See the module documentation for the protocol
+pub struct ExternalPlayer {
+    name: String,
+    child: Child,
+    connection: Connection,
+    /// Why the bot was stopped, once it has been
+    failure: Option<ExternalError>,
+    /// Plays for the bot once it has stopped, and replaces its illegal replies
+    fallback: HeuristicPlayer,
+    /// Lines the bot has written to stderr, read on their own thread
+    stderr: Receiver<String>,
+    /// Where the bot's stderr and the reasons its replies were rejected are shown
+    output: Output,
+    /// Whether the bot has called UNO this turn, since it is only accepted once a turn
+    uno_called: OncePerTurn,
+}
+
+impl ExternalPlayer {
+    /// Runs the command line, split on whitespace, and waits for the bot to be ready
+    pub fn start(command_line: &str, timeout: Duration, output: Output) -> Result<ExternalPlayer, ExternalError> {
+        let mut words = command_line.split_whitespace();
+        let program: &str = match words.next() {
+            Some(x) => x,
+            None => return Err(ExternalError::Start("no command given".to_owned())),
+        };
+        let mut child: Child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| ExternalError::Start(format!("{}: {}", program, err)))?;
+
+        let stdout: ChildStdout = child.stdout.take().expect("Expected the bot's stdout to be piped");
+        let stdin: ChildStdin = child.stdin.take().expect("Expected the bot's stdin to be piped");
+        let stderr: ChildStderr = child.stderr.take().expect("Expected the bot's stderr to be piped");
+        let (sender, stderr_lines) = channel::<String>();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut player: ExternalPlayer = ExternalPlayer {
+            name: program.to_owned(),
+            child: child,
+            connection: Connection::new(stdout, stdin, timeout),
+            failure: None,
+            fallback: HeuristicPlayer::new(Difficulty::Medium),
+            stderr: stderr_lines,
+            output: output,
+            uno_called: OncePerTurn::new(),
+        };
+        player.connection.send(&format!("{} {}", PROTOCOL_HEADER, PROTOCOL_VERSION))?;
+        let reply: String = player.connection.receive()?;
+        let mut words = reply.splitn(2, ' ');
+        if words.next() != Some("ready") {
+            return Err(ExternalError::NotReady(reply));
+        }
+        if let Some(name) = words.next().map(str::trim).filter(|name| !name.is_empty()) {
+            player.name = name.to_owned();
+        }
+        player.show_stderr();
+        Ok(player)
+    }
+
+    /// Returns the name the bot gave, or its program if it gave none
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns why the bot was stopped, if it has been
+    #[cfg(test)]
+    pub fn failure(&self) -> Option<&ExternalError> {
+        self.failure.as_ref()
+    }
+
+    /// Shows what the bot has written to stderr since this was last called
+    fn show_stderr(&mut self) {
+        while let Ok(line) = self.stderr.try_recv() {
+            self.output.say(&format!("{}: {}", self.name, line));
+        }
+    }
+
+    /// Sends the view and a request, and returns the reply.
+    /// Returns None once the bot has stopped
+    fn request(&mut self, view: &PlayerView, request: &str) -> Option<String> {
+        if self.failure.is_some() {
+            return None;
+        }
+        let result: Result<String, ExternalError> = self.connection.send_view(view, request)
+            .and_then(|_| self.connection.receive());
+        self.show_stderr();
+        match result {
+            Ok(reply) => Some(reply),
+            Err(err) => {
+                self.output.say(&format!("{} has stopped and is replaced by a {} bot: {}", self.name, Difficulty::Medium, err));
+                self.stop();
+                self.failure = Some(err);
+                None
+            },
+        }
+    }
+
+    /// Returns the action in the reply if the bot may take it right now
+    fn check_reply(&mut self, view: &PlayerView, reply: &str) -> Option<Action> {
+        let allowed: Result<Action, &str> = Action::from_code(reply).and_then(|action| {
+            let legal: bool = match action {
+                Action::CallUno => view.is_my_turn() && view.hand.len() == 2 && self.uno_called.first_time(view),
+                Action::Catch(catcher, player) => catcher == view.player && player != view.player && view.uno_vulnerable == Some(player) && view.hand_sizes[player] == 1,
+                _ => view.legal_actions.contains(&action),
+            };
+            if legal { Ok(action) } else { Err("Action is not legal right now") }
+        });
+        match allowed {
+            Ok(action) => Some(action),
+            Err(err) => {
+                self.output.say(&format!("{} replied {:?}: {}", self.name, reply, err));
+                None
+            },
+        }
+    }
+
+    /// Asks the bot to quit, then ends its process if it has not exited shortly after
+    fn stop(&mut self) {
+        let _ = self.connection.send("quit");
+        self.connection.close();
+        let start: Instant = Instant::now();
+        while start.elapsed() < Duration::from_millis(100) {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for ExternalPlayer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Player for ExternalPlayer {
+    fn choose_action(&mut self, view: &PlayerView) -> Action {
+        let reply: Option<String> = self.request(view, "go");
+        match reply.and_then(|reply| self.check_reply(view, &reply)) {
+            Some(action) => action,
+            None => self.fallback.choose_action(view),
+        }
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        // The bot only ever plays wild cards with a color, so a wild without one came from the fallback
+        if view.pending != Some(Pending::ChooseColor) {
+            return self.fallback.choose_color(view);
+        }
+        let reply: Option<String> = self.request(view, "go");
+        match reply.and_then(|reply| self.check_reply(view, &reply)) {
+            Some(Action::ChooseColor(color)) => color,
+            _ => self.fallback.choose_color(view),
+        }
+    }
+
+    fn react(&mut self, view: &PlayerView) -> Option<Action> {
+        if self.failure.is_some() {
+            return self.fallback.react(view);
+        }
+        if !view.can_react() {
+            return None;
+        }
+        match self.request(view, "react") {
+            Some(ref reply) if reply == "none" => None,
+            Some(reply) => self.check_reply(view, &reply),
+            None => self.fallback.react(view),
+        }
+    }
+}
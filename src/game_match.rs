@@ -0,0 +1,164 @@
+extern crate rand;
+
+use cards::Card;
+use game_state::{GameState, RuleError};
+use replay::Replay;
+use rules::RuleSet;
+use save::{LoadError, SaveReader, SaveWriter, read_save_file, write_save_file};
+
+/// The score a player must reach to win a match under the official rules
+pub const DEFAULT_TARGET_SCORE: u32 = 500;
+
+/// A Match is a series of rounds, each one a GameState. The winner of each round
+/// scores the points left in every other player's hand, and the match ends once
+/// a player's total reaches the target score.
+/// The dealer rotates each round, and the player after the dealer goes first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub round: GameState,
+    scores: Vec<u32>,
+    target_score: u32,
+    dealer: usize,
+    round_number: usize,
+    rules: RuleSet,
+    seed: u64,
+}
+
+impl Match {
+    /// Constructs a new Match with a random seed
+    pub fn new(num_players: usize, rules: RuleSet, target_score: u32) -> Result<Match, RuleError> {
+        Match::with_seed(num_players, rand::random(), rules, target_score)
+    }
+
+    /// Constructs a new Match whose rounds are all determined by `seed`.
+    /// Fails if a round cannot be dealt to that many players
+    pub fn with_seed(num_players: usize, seed: u64, rules: RuleSet, target_score: u32) -> Result<Match, RuleError> {
+        let dealer: usize = 0;
+        Ok(Match {
+            // Dealing rejects a match without players
+            round: GameState::deal(num_players, seed, rules, (dealer + 1) % num_players.max(1))?,
+            scores: vec!(0; num_players),
+            target_score: target_score,
+            dealer: dealer,
+            round_number: 0,
+            rules: rules,
+            seed: seed,
+        })
+    }
+
+    /// Scores the current round, which must have a winner.
+    /// Adds the points in the losers' hands to the winner's score and returns them
+    pub fn score_round(&mut self) -> u32 {
+        let winner: usize = self.round.winner().expect("Expected the round to have a winner");
+        let points: u32 = round_points(&self.round, winner);
+        self.scores[winner] += points;
+        points
+    }
+
+    /// Deals the next round, rotating the dealer
+    pub fn next_round(&mut self) {
+        let num_players: usize = self.scores.len();
+        self.dealer = (self.dealer + 1) % num_players;
+        self.round_number += 1;
+        let seed: u64 = self.seed.wrapping_add(self.round_number as u64);
+        self.round = GameState::deal(num_players, seed, self.rules, (self.dealer + 1) % num_players)
+            .expect("Expected the first round to have been dealt to as many players");
+    }
+
+    /// Returns the player who has reached the target score, if any
+    pub fn winner(&self) -> Option<usize> {
+        self.scores.iter().position(|score| *score >= self.target_score)
+    }
+
+    pub fn scores(&self) -> &Vec<u32> {
+        &self.scores
+    }
+
+    pub fn target_score(&self) -> u32 {
+        self.target_score
+    }
+
+    #[cfg(test)]
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    /// Returns the number of rounds dealt before the current one
+    pub fn round_number(&self) -> usize {
+        self.round_number
+    }
+
+    /// Starts a replay of the current round, which must not have had any actions applied
+    pub fn start_replay(&self) -> Replay {
+        let num_players: usize = self.scores.len();
+        Replay::new(num_players, self.round.seed(), self.rules, (self.dealer + 1) % num_players)
+    }
+
+    /// Returns this match, including the round in progress, as the text of a save file
+    pub fn save(&self) -> String {
+        let mut writer: SaveWriter = SaveWriter::new();
+        writer.field("target", self.target_score);
+        writer.field("dealer", self.dealer);
+        writer.field("round", self.round_number);
+        writer.field("match-seed", self.seed);
+        let scores: Vec<String> = self.scores.iter().map(|score| score.to_string()).collect();
+        writer.field("scores", scores.join(" "));
+        self.round.write_save(&mut writer);
+        writer.finish()
+    }
+
+    /// Reads a match from the text of a save file made by `save`
+    pub fn load(text: &str) -> Result<Match, LoadError> {
+        let mut reader: SaveReader = SaveReader::new(text)?;
+        let target_score: u32 = reader.parse("target")?;
+        let target_line: usize = reader.last_line();
+        let (dealer_line, dealer) = reader.field("dealer")?;
+        let round_number: usize = reader.parse("round")?;
+        let seed: u64 = reader.parse("match-seed")?;
+        let scores: Vec<u32> = reader.list("scores")?;
+        let scores_line: usize = reader.last_line();
+        let round: GameState = GameState::read_save(&mut reader)?;
+        reader.finish()?;
+
+        if target_score == 0 {
+            return Err(LoadError::Invalid(target_line, "the target score must be above 0".to_owned()));
+        }
+        if scores.len() != round.players.len() {
+            return Err(LoadError::Invalid(scores_line, "there must be one score per player".to_owned()));
+        }
+        let dealer: usize = match dealer.parse::<usize>() {
+            Ok(x) if x < scores.len() => x,
+            _ => return Err(LoadError::Invalid(dealer_line, "no such player".to_owned())),
+        };
+        Ok(Match {
+            rules: *round.rules(),
+            round: round,
+            scores: scores,
+            target_score: target_score,
+            dealer: dealer,
+            round_number: round_number,
+            seed: seed,
+        })
+    }
+
+    /// Saves this match to a file
+    pub fn save_to_file(&self, path: &str) -> Result<(), LoadError> {
+        write_save_file(path, &self.save())
+    }
+
+    /// Loads a match from a file
+    pub fn load_from_file(path: &str) -> Result<Match, LoadError> {
+        Match::load(&read_save_file(path)?)
+    }
+}
+
+/// Returns the points the given player would score from every other player's hand
+pub fn round_points(game_state: &GameState, winner: usize) -> u32 {
+    let mut points: u32 = 0;
+    for player in 0..game_state.players.len() {
+        if player != winner {
+            points += game_state.players.get_player(player).iter().map(Card::points).sum::<u32>();
+        }
+    }
+    points
+}
@@ -1,70 +1,388 @@
 extern crate rand;
 
 use cards::*;
+use events::GameEvent;
+use player_view::PlayerView;
+use rules::RuleSet;
+use save::{LoadError, SaveReader, SaveWriter};
+use std::fmt;
+use self::rand::SeedableRng;
+use self::rand::rngs::Xoshiro256PlusPlus;
 
 const STARTING_HAND_SIZE: usize = 7;
 
+/// The most players a deck can be dealt to. Every hand is dealt with at least one card
+/// that is not wild left over for the starting card, as there are 8 wild cards
+pub const MAX_PLAYERS: usize = 14;
+
+/// The colors a wild card may be played as
+pub const WILD_COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+
 /// A GameState holds all of the information needed for an Uno game_state
 /// This includes the players (each with their deck, and the current player)
 /// the deck of cards to play on, the deck of cards to draw from, and the turn turn
 /// Note that players may have zero cards (which means they have won) in their hand
+/// All shuffling is determined by the seed stored on the state, so two games with the
+/// same seed and the same actions are identical
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GameState {
     pub turn_order: TurnOrder,
     pub players: Players,
     draw_deck: Vec<Card>,
     play_deck: Vec<Card>,
+    winner: Option<usize>,
+    pending: Option<Pending>,
+    uno_called: bool,
+    uno_vulnerable: Option<usize>,
+    /// The number of turns started this round, so that one turn can be told from the next
+    turn: usize,
+    /// True if the last WildPlus4 was played while holding a card of the active color
+    wild_plus4_bluffed: bool,
+    events: Vec<GameEvent>,
+    rules: RuleSet,
+    seed: u64,
+    /// The number of times the draw deck has been shuffled
+    shuffles: u64,
 }
 
 impl GameState {
-    /// Constructs a new GameState
+    /// Constructs a new GameState with a random seed
     /// This shuffles the deck, deals cards to players,
     /// and ensures that the top card on play_deck is not a wild card
-    pub fn new(num_players: usize) -> GameState {
+    #[cfg(test)]
+    pub fn new(num_players: usize, rules: RuleSet) -> Result<GameState, RuleError> {
+        GameState::with_seed(num_players, rand::random(), rules)
+    }
+
+    /// Constructs a new GameState whose shuffles are determined by `seed`
+    pub fn with_seed(num_players: usize, seed: u64, rules: RuleSet) -> Result<GameState, RuleError> {
+        GameState::deal(num_players, seed, rules, 0)
+    }
+
+    /// Constructs a new GameState whose shuffles are determined by `seed`,
+    /// where `first_player` takes the first turn.
+    /// Fails if there are no players, or more than `MAX_PLAYERS`
+    pub fn deal(num_players: usize, seed: u64, rules: RuleSet, first_player: usize) -> Result<GameState, RuleError> {
         use game_state::TurnOrder::*;
+        if num_players == 0 || num_players > MAX_PLAYERS {
+            return Err(RuleError::PlayerCount);
+        }
         let mut game_state = GameState {
             turn_order: Normal,
             play_deck: Vec::new(),
             draw_deck: get_deck(),
             players: Players::new(num_players),
+            winner: None,
+            pending: None,
+            uno_called: false,
+            uno_vulnerable: None,
+            turn: 0,
+            wild_plus4_bluffed: false,
+            events: Vec::new(),
+            rules: rules,
+            seed: seed,
+            shuffles: 0,
         };
 
+        game_state.players.current_player = first_player;
         game_state.shuffle();
 
         // Deal cards
         for i in 0..num_players {
             for _ in 0..STARTING_HAND_SIZE {
-                let card: Card = game_state.pop_draw_deck();
+                let card: Card = game_state.try_pop_draw_deck().ok_or(RuleError::PlayerCount)?;
                 game_state.players.get_hand(i).push(card);
             }
         }
-        game_state.deal_first_play_card();
+        game_state.deal_first_play_card()?;
 
-        game_state
+        Ok(game_state)
+    }
+
+    /// Applies an action for the current player. A played card must be in the
+    /// current player's hand at the given index, must be playable onto the top card,
+    /// and must have a color chosen if it is a wild card. On success the card is
+    /// removed from the hand and its effect is applied.
+    /// A player who goes down to one card without calling UNO first may be caught
+    /// until the next action is taken
+    pub fn apply(&mut self, action: Action) -> Result<Outcome, RuleError> {
+        if self.winner.is_some() {
+            return Err(RuleError::GameOver);
+        }
+        match action {
+            Action::CallUno => {
+                if self.players.get_current_player().len() > 2 {
+                    return Err(RuleError::NotAllowed);
+                }
+                self.uno_called = true;
+                let player: usize = self.players.current_player;
+                self.events.push(GameEvent::UnoCalled(player));
+                return Ok(Outcome::Continue)
+            },
+            Action::Catch(catcher, player) => {
+                if catcher >= self.players.len() {
+                    return Err(RuleError::NoSuchPlayer);
+                }
+                // Nobody may catch themselves
+                if catcher == player || self.uno_vulnerable != Some(player) || self.players.get_player(player).len() != 1 {
+                    return Err(RuleError::NotAllowed);
+                }
+                self.uno_vulnerable = None;
+                self.events.push(GameEvent::UnoCaught(player));
+                self.draw_n(player, 2);
+                return Ok(Outcome::Continue)
+            },
+            _ => {},
+        }
+
+        let vulnerable: Option<usize> = self.uno_vulnerable.take();
+        let result: Result<Outcome, RuleError> = self.apply_turn(action);
+        match result {
+            Ok(_) => self.uno_called = false,
+            Err(_) => self.uno_vulnerable = vulnerable,
+        }
+        result
+    }
+
+    /// Applies an action that is part of a turn (Anything other than calling or catching UNO)
+    fn apply_turn(&mut self, action: Action) -> Result<Outcome, RuleError> {
+        match action {
+            Action::Play(card, index) => {
+                let player: usize = self.players.current_player;
+                self.check_card(player, &card, index)?;
+                let allowed: bool = match self.pending {
+                    None => playable_card(&card, self.top_card()),
+                    Some(Pending::DrawnCard(drawn)) => drawn == index && playable_card(&card, self.top_card()),
+                    Some(Pending::DrawPenalty(_)) => stackable_card(&card, self.top_card()),
+                    Some(Pending::SwapHands) | Some(Pending::Challenge(_)) | Some(Pending::ChooseColor) => {
+                        return Err(RuleError::NotAllowed)
+                    },
+                };
+                if !allowed {
+                    return Err(RuleError::NotPlayable);
+                }
+                // A stacked penalty is carried over into the card's effect
+                if let Some(Pending::DrawnCard(_)) = self.pending {
+                    self.pending = None;
+                }
+                return Ok(self.play_from_hand(card, index));
+            },
+            Action::Draw => {
+                match self.pending {
+                    None => {
+                        if !self.legal_actions().contains(&Action::Draw) {
+                            return Err(RuleError::MustPlay);
+                        }
+                        self.draw_card();
+                    },
+                    Some(Pending::DrawPenalty(num_cards)) => {
+                        self.pending = None;
+                        let player: usize = self.players.current_player;
+                        self.draw_n(player, num_cards);
+                        self.skip_current_player();
+                    },
+                    Some(_) => return Err(RuleError::NotAllowed),
+                }
+                return Ok(Outcome::Continue)
+            },
+            Action::Pass => {
+                match self.pending {
+                    Some(Pending::DrawnCard(_)) if !self.rules.forced_play => {
+                        self.pending = None;
+                        self.events.push(GameEvent::Passed(self.players.current_player));
+                        self.next_player();
+                        return Ok(Outcome::Continue)
+                    },
+                    Some(Pending::DrawnCard(_)) => return Err(RuleError::MustPlay),
+                    _ => return Err(RuleError::NotAllowed),
+                }
+            },
+            Action::Challenge(challenge) => {
+                let offender: usize = match self.pending {
+                    Some(Pending::Challenge(offender)) => offender,
+                    _ => return Err(RuleError::NotAllowed),
+                };
+                self.pending = None;
+                let player: usize = self.players.current_player;
+                if challenge {
+                    self.events.push(GameEvent::Challenged(player, offender, self.wild_plus4_bluffed));
+                }
+                if !challenge {
+                    self.draw_n(player, 4);
+                    self.skip_current_player();
+                } else if self.wild_plus4_bluffed {
+                    // The challenger is not skipped
+                    self.draw_n(offender, 4);
+                } else {
+                    self.draw_n(player, 6);
+                    self.skip_current_player();
+                }
+                return Ok(Outcome::Continue)
+            },
+            Action::ChooseColor(color) => {
+                if self.pending != Some(Pending::ChooseColor) {
+                    return Err(RuleError::NotAllowed);
+                }
+                if color == Color::Any {
+                    return Err(RuleError::WildColorMissing);
+                }
+                self.pending = None;
+                let top_card: &mut Card = self.play_deck.last_mut().expect("Expected at least one card in the play_deck");
+                top_card.card_type = CardType::Wild(color);
+                self.events.push(GameEvent::ColorChosen(self.players.current_player, color));
+                return Ok(Outcome::Continue)
+            },
+            Action::SwapHands(target) => {
+                if self.pending != Some(Pending::SwapHands) {
+                    return Err(RuleError::NotAllowed);
+                }
+                if target >= self.players.len() || target == self.players.current_player {
+                    return Err(RuleError::NoSuchPlayer);
+                }
+                self.pending = None;
+                let current: usize = self.players.current_player;
+                self.players.players.swap(current, target);
+                self.events.push(GameEvent::HandsSwapped(current, target));
+                self.next_player();
+                return Ok(Outcome::Continue)
+            },
+            Action::JumpIn(player, index) => {
+                if !self.rules.jump_in || self.pending.is_some() {
+                    return Err(RuleError::NotAllowed);
+                }
+                if player >= self.players.len() {
+                    return Err(RuleError::NoSuchPlayer);
+                }
+                let card: Card = match self.players.players[player].get(index) {
+                    Some(card) => *card,
+                    None => return Err(RuleError::NotInHand),
+                };
+                if card.color == Color::Any || card != *self.top_card() {
+                    return Err(RuleError::NotPlayable);
+                }
+                // An UNO call belongs to the player who was interrupted
+                if player != self.players.current_player {
+                    self.uno_called = false;
+                }
+                self.players.current_player = player;
+                self.turn += 1;
+                self.events.push(GameEvent::JumpedIn(player));
+                return Ok(self.play_from_hand(card, index));
+            },
+            Action::CallUno | Action::Catch(_, _) => unreachable!(),
+        }
+    }
+
+    /// Checks that `card` is at `index` in the given player's hand,
+    /// and that a color has been chosen if it is a wild card
+    fn check_card(&self, player: usize, card: &Card, index: usize) -> Result<(), RuleError> {
+        match self.players.players[player].get(index) {
+            Some(in_hand) if same_face(in_hand, card) => {},
+            _ => return Err(RuleError::NotInHand),
+        }
+        if chosen_color(card) == Some(Color::Any) {
+            return Err(RuleError::WildColorMissing);
+        }
+        Ok(())
+    }
+
+    /// Removes an already checked card from the current player's hand and plays it,
+    /// ending the game if that was their last card
+    fn play_from_hand(&mut self, card: Card, index: usize) -> Outcome {
+        let player: usize = self.players.current_player;
+        self.players.get_current_player_mut().remove(index);
+        self.events.push(GameEvent::CardPlayed(player, card));
+        if let Some(color) = chosen_color(&card) {
+            self.events.push(GameEvent::ColorChosen(player, color));
+        }
+        if self.players.get_current_player().len() == 1 && !self.uno_called {
+            self.uno_vulnerable = Some(self.players.current_player);
+        }
+        if self.players.get_current_player().is_empty() {
+            let winner: usize = self.players.current_player;
+            self.play_deck.push(card);
+            self.pending = None;
+            self.winner = Some(winner);
+            self.events.push(GameEvent::PlayerWon(winner));
+            return Outcome::Won(winner)
+        }
+        self.play_card(card);
+        Outcome::Continue
+    }
+
+    /// Returns the index of the player who emptied their hand, if any
+    pub fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    /// Returns the player who may be caught for not calling UNO, if any
+    pub fn uno_vulnerable(&self) -> Option<usize> {
+        self.uno_vulnerable
+    }
+
+    /// Returns the number of turns started this round. A jump-in starts a new turn
+    pub fn turn(&self) -> usize {
+        self.turn
     }
 
-    /// Play a card onto the deck. If this card cannot be played then
-    /// this function panics (Use `playable_card` before to ensure the card may be played)
-    pub fn play_card(&mut self, card: &mut Card) -> &mut GameState {
+    /// Returns the decision the current player must make, if any
+    pub fn pending(&self) -> Option<Pending> {
+        self.pending
+    }
+
+    /// Returns the house rules this game is played with
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Returns every event that has happened since the log was last drained
+    pub fn events(&self) -> &Vec<GameEvent> {
+        &self.events
+    }
+
+    /// Removes and returns every event in the log
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+
+    /// Returns the seed this game was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Play a card onto the deck and apply its effect.
+    /// The card is assumed to have already been checked by `apply`
+    fn play_card(&mut self, card: Card) {
         use cards::CardType::*;
-        if playable_card(card, self.top_card()) {
-            self.play_deck.push(*card);
-            match card.card_type {
-                Reverse => self.reverse(),
-                Skip => self.skip(),
-                Plus2 => self.plus_n(2),
-                WildPlus4(_) => self.plus_n(4),
-                _ => self.next_player(),
-            }
-            return self
-        } else {
-            panic!()
+        let active_color: Color = active_color(self.top_card());
+        self.play_deck.push(card);
+        match card.card_type {
+            Reverse => self.reverse(),
+            Skip => self.skip(),
+            Plus2 => self.plus_n(2),
+            WildPlus4(_) if !self.rules.stacking => {
+                // A WildPlus4 may only be played without a card of the active color
+                let offender: usize = self.players.current_player;
+                self.wild_plus4_bluffed = self.players.get_current_player().iter().any(|x| x.color == active_color);
+                self.next_player();
+                self.pending = Some(Pending::Challenge(offender));
+            },
+            WildPlus4(_) => self.plus_n(4),
+            Number(7) if self.rules.seven_zero => self.pending = Some(Pending::SwapHands),
+            Number(0) if self.rules.seven_zero => {
+                let forward: bool = self.turn_order == TurnOrder::Normal;
+                self.players.rotate_hands(forward);
+                self.events.push(GameEvent::HandsRotated(self.turn_order));
+                self.next_player();
+            },
+            _ => self.next_player(),
         }
     }
 
     /// Goes to the next player (This goes backwards if a reverse is in play)
     fn next_player(&mut self) {
         use self::TurnOrder::*;
+        self.turn += 1;
         match self.turn_order {
             Normal => self.players.next_player(),
             Reverse => self.players.previous_player(),
@@ -72,78 +390,464 @@ impl GameState {
     }
 
     /// Draws a card from the draw_deck (Refilling it if need be)
-    /// Then, goes to the next player
-    pub fn draw_card(&mut self) {
-        let card: Card = self.pop_draw_deck();
-        self.players.get_current_player_mut().push(card);
-        self.next_player();
+    /// If `draw_until_playable` is set, this keeps drawing until a playable card is drawn.
+    /// Then, goes to the next player, unless `play_drawn_card` is set and the
+    /// drawn card is playable
+    fn draw_card(&mut self) {
+        let player: usize = self.players.current_player;
+        let mut num_drawn: usize = 0;
+        let mut keep_turn: bool = false;
+        while let Some(card) = self.try_pop_draw_deck() {
+            self.players.get_current_player_mut().push(card);
+            num_drawn += 1;
+            let playable: bool = playable_card(&card, self.top_card());
+            if playable && self.rules.play_drawn_card {
+                let index: usize = self.players.get_current_player().len() - 1;
+                self.pending = Some(Pending::DrawnCard(index));
+                keep_turn = true;
+                break;
+            }
+            if playable || !self.rules.draw_until_playable {
+                break;
+            }
+        }
+        self.events.push(GameEvent::CardsDrawn(player, num_drawn));
+        if !keep_turn {
+            self.next_player();
+        }
     }
 
-    /// Get the top card of the `play_deck`. If it's empty then refill it
-    fn pop_draw_deck(&mut self) -> Card {
-        match self.draw_deck.pop() {
-            Some(card) => return card,
+    /// Draws `num_cards` cards into the given player's hand
+    fn draw_n(&mut self, player: usize, num_cards: usize) {
+        let mut cards: Vec<Card> = Vec::new();
+        for _ in 0..num_cards {
+            match self.try_pop_draw_deck() {
+                Some(card) => cards.push(card),
+                None => break,
+            }
+        }
+        self.events.push(GameEvent::CardsDrawn(player, cards.len()));
+        let this_hand: &mut Vec<Card> = self.players.get_hand(player);
+        this_hand.append(&mut cards);
+    }
+
+    /// Get the top card of the `draw_deck`, refilling it if need be.
+    /// Returns None if every card other than the top card is in a player's hand
+    fn try_pop_draw_deck(&mut self) -> Option<Card> {
+        if self.draw_deck.is_empty() && self.play_deck.len() > 1 {
+            self.refill();
+        }
+        self.draw_deck.pop()
+    }
+
+    /// Lists every action the current player may take. Wild cards appear once
+    /// for each color they could be played as. While no decision is pending,
+    /// `Action::Draw` is last unless `forced_play` forbids it.
+    /// Jump-ins are listed separately by `jump_in_actions`, and calling or
+    /// catching UNO is never listed
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if self.winner.is_some() {
+            return actions;
+        }
+        let top_card: &Card = self.top_card();
+        let hand: &Vec<Card> = self.players.get_current_player();
+        match self.pending {
             None => {
-                self.refill();
-                return self.pop_draw_deck();
+                for (index, card) in hand.iter().enumerate() {
+                    if playable_card(card, top_card) {
+                        push_plays(&mut actions, card, index);
+                    }
+                }
+                if actions.is_empty() || !self.rules.forced_play {
+                    actions.push(Action::Draw);
+                }
+            },
+            Some(Pending::DrawnCard(index)) => {
+                push_plays(&mut actions, &hand[index], index);
+                if !self.rules.forced_play {
+                    actions.push(Action::Pass);
+                }
+            },
+            Some(Pending::DrawPenalty(_)) => {
+                for (index, card) in hand.iter().enumerate() {
+                    if stackable_card(card, top_card) {
+                        push_plays(&mut actions, card, index);
+                    }
+                }
+                actions.push(Action::Draw);
+            },
+            Some(Pending::Challenge(_)) => {
+                actions.push(Action::Challenge(true));
+                actions.push(Action::Challenge(false));
+            },
+            Some(Pending::ChooseColor) => {
+                for color in WILD_COLORS.iter() {
+                    actions.push(Action::ChooseColor(*color));
+                }
+            },
+            Some(Pending::SwapHands) => {
+                for player in 0..self.players.len() {
+                    if player != self.players.current_player {
+                        actions.push(Action::SwapHands(player));
+                    }
+                }
+            },
+        }
+        actions
+    }
+
+    /// Lists the jump-ins the given player may make out of turn
+    pub fn jump_in_actions(&self, player: usize) -> Vec<Action> {
+        let mut actions: Vec<Action> = Vec::new();
+        if !self.rules.jump_in || self.pending.is_some() || self.winner.is_some() {
+            return actions;
+        }
+        for (index, card) in self.players.players[player].iter().enumerate() {
+            if card.color != Color::Any && card == self.top_card() {
+                actions.push(Action::JumpIn(player, index));
             }
         }
+        actions
+    }
+
+    /// Returns every card played so far, with the top card last
+    pub fn discard_pile(&self) -> &Vec<Card> {
+        &self.play_deck
+    }
+
+    /// Returns the number of cards left to draw
+    pub fn draw_pile_len(&self) -> usize {
+        self.draw_deck.len()
     }
 
     pub fn top_card(&self) -> &Card {
         return self.play_deck.last().expect("Expected at least one card in the play_deck");
     }
 
-    fn deal_first_play_card(&mut self) {
-        // Make sure top card is not a wild card
+    /// Turns over the first card of the `play_deck` and applies its effect to the first player.
+    /// A Skip skips them, a Reverse reverses the turn order so the dealer goes first,
+    /// a Plus2 makes them draw 2 and skips them, and a Wild lets them choose its color.
+    /// A WildPlus4 is shuffled back into the deck.
+    /// Fails if every card is dealt without one to start with
+    fn deal_first_play_card(&mut self) -> Result<(), RuleError> {
+        use cards::CardType::*;
+        if self.rules.plain_starting_card {
+            // Make sure top card is not a wild card
+            loop {
+                let card: Card = self.try_pop_draw_deck().ok_or(RuleError::PlayerCount)?;
+                self.play_deck.push(card);
+                if self.top_card().color != Color::Any {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+
         loop {
-            let card: Card = self.pop_draw_deck();
+            let card: Card = self.try_pop_draw_deck().ok_or(RuleError::PlayerCount)?;
+            if let WildPlus4(_) = card.card_type {
+                self.draw_deck.push(card);
+                self.shuffle();
+                continue;
+            }
             self.play_deck.push(card);
-            if self.top_card().color != Color::Any {
-                break;
+            match card.card_type {
+                Skip => self.skip_current_player(),
+                Reverse => {
+                    self.turn_order = TurnOrder::Reverse;
+                    self.events.push(GameEvent::DirectionReversed(TurnOrder::Reverse));
+                    self.next_player();
+                },
+                Plus2 => {
+                    let player: usize = self.players.current_player;
+                    self.draw_n(player, 2);
+                    self.skip_current_player();
+                },
+                Wild(_) => self.pending = Some(Pending::ChooseColor),
+                _ => {},
             }
+            return Ok(());
         }
     }
 
+    /// Reverses the turn order. With two players this acts like a Skip,
+    /// so the same player goes again
     fn reverse(&mut self) {
         use self::TurnOrder::*;
         match self.turn_order {
             Normal => self.turn_order = Reverse,
             Reverse => self.turn_order = Normal,
         }
-        self.next_player();
+        self.events.push(GameEvent::DirectionReversed(self.turn_order));
+        if self.players.len() == 2 {
+            self.skip();
+        } else {
+            self.next_player();
+        }
     }
 
     fn skip(&mut self) {
         self.next_player();
+        self.skip_current_player();
+    }
+
+    /// Skips the current player's turn
+    fn skip_current_player(&mut self) {
+        self.events.push(GameEvent::TurnSkipped(self.players.current_player));
         self.next_player();
     }
 
+    /// Makes the next player draw `num_cards` and skips them.
+    /// If `stacking` is set, the next player instead faces a penalty they may stack onto
+    /// (This replaces the WildPlus4 challenge)
     fn plus_n(&mut self, num_cards: usize) {
         self.next_player();
-        {
-            let mut cards: Vec<Card> = Vec::new();
-            for _ in 0..num_cards {
-                cards.push(self.pop_draw_deck());
-            }
-            let this_hand: &mut Vec<Card> = self.players.get_current_player_mut();
-            this_hand.append(&mut cards);
+        if self.rules.stacking {
+            let total: usize = match self.pending {
+                Some(Pending::DrawPenalty(n)) => n + num_cards,
+                _ => num_cards,
+            };
+            self.pending = Some(Pending::DrawPenalty(total));
+            return;
         }
-        self.next_player();
+        let player: usize = self.players.current_player;
+        self.draw_n(player, num_cards);
+        self.skip_current_player();
     }
 
+    /// Shuffles every card under the top card of the `play_deck` back into the `draw_deck`
     fn refill(&mut self) {
-        if self.draw_deck.len() != 0 {
+        if !self.draw_deck.is_empty() {
             panic!("Draw deck not empty");
-        } 
-        self.draw_deck.append(&mut self.play_deck);
-        self.deal_first_play_card();
-        println!("Refilled draw deck");
+        }
+        let top_card: Card = self.play_deck.pop().expect("Expected at least one card in the play_deck");
+        for card in self.play_deck.drain(..) {
+            // Wild cards go back into the deck without a chosen color
+            self.draw_deck.push(without_chosen_color(&card));
+        }
+        self.play_deck.push(top_card);
+        self.shuffle();
+        self.events.push(GameEvent::DeckReshuffled);
     }
 
     fn shuffle(&mut self) {
-        use self::rand::Rng;
-        rand::thread_rng().shuffle(&mut self.draw_deck.as_mut_slice());
+        use self::rand::seq::SliceRandom;
+        // Each shuffle gets its own RNG derived from the seed, so a saved game
+        // only needs to remember how many shuffles there have been
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(self.seed ^ self.shuffles.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        self.shuffles += 1;
+        self.draw_deck.shuffle(&mut rng);
+    }
+}
+
+impl GameState {
+    /// Builds a GameState that matches everything `view` shows, for searching ahead.
+    /// The cards the viewer cannot see are shuffled by `seed` and dealt to the other
+    /// hands, with the rest left in the draw pile. Where possible, a player is not
+    /// dealt a color that `lacks` says they do not hold
+    pub fn determinize(view: &PlayerView, lacks: &[Vec<Color>], seed: u64) -> GameState {
+        use self::rand::seq::SliceRandom;
+        let mut unseen: Vec<Card> = get_deck();
+        for card in view.discard_pile.iter().chain(view.hand.iter()) {
+            let card: Card = without_chosen_color(card);
+            if let Some(i) = unseen.iter().position(|x| *x == card) {
+                unseen.swap_remove(i);
+            }
+        }
+        unseen.shuffle(&mut Xoshiro256PlusPlus::seed_from_u64(seed));
+
+        let mut players: Players = Players::new(view.num_players());
+        players.current_player = view.current_player;
+        for player in 0..view.num_players() {
+            if player == view.player {
+                players.players[player] = view.hand.clone();
+                continue;
+            }
+            let lacking: &[Color] = lacks.get(player).map_or(&[], |colors| &colors[..]);
+            for _ in 0..view.hand_sizes[player] {
+                if unseen.is_empty() {
+                    break;
+                }
+                let i: usize = unseen.iter().position(|card| !lacking.contains(&card.color)).unwrap_or(0);
+                players.players[player].push(unseen.remove(i));
+            }
+        }
+
+        // A WildPlus4 waiting to be challenged was a bluff if its player now holds the old color
+        let wild_plus4_bluffed: bool = match view.pending {
+            Some(Pending::Challenge(offender)) if view.discard_pile.len() >= 2 => {
+                let color: Color = active_color(&view.discard_pile[view.discard_pile.len() - 2]);
+                players.players[offender].iter().any(|card| card.color == color)
+            },
+            _ => false,
+        };
+        GameState {
+            turn_order: view.turn_order,
+            players: players,
+            draw_deck: unseen,
+            play_deck: view.discard_pile.clone(),
+            winner: view.winner,
+            pending: view.pending,
+            uno_called: false,
+            uno_vulnerable: view.uno_vulnerable,
+            turn: view.turn,
+            wild_plus4_bluffed: wild_plus4_bluffed,
+            events: Vec::new(),
+            rules: view.rules,
+            seed: seed,
+            // Dealing the unseen cards used the seed's first shuffle
+            shuffles: 1,
+        }
+    }
+
+    /// Writes this game's fields to a save file. See the `save` module for the format
+    pub fn write_save(&self, writer: &mut SaveWriter) {
+        writer.field("seed", self.seed);
+        writer.field("shuffles", self.shuffles);
+        writer.rules("rules", &self.rules);
+        writer.turn_order("turn-order", self.turn_order);
+        writer.field("current-player", self.players.current_player);
+        writer.field("turn", self.turn);
+        writer.optional("winner", self.winner);
+        writer.field("pending", pending_code(self.pending));
+        writer.field("uno-called", self.uno_called);
+        writer.optional("uno-vulnerable", self.uno_vulnerable);
+        writer.field("bluffed", self.wild_plus4_bluffed);
+        writer.cards("draw-deck", &self.draw_deck);
+        writer.cards("play-deck", &self.play_deck);
+        writer.field("hands", self.players.len());
+        for hand in self.players.players.iter() {
+            writer.cards("hand", hand);
+        }
+    }
+
+    /// Reads a game written by `write_save`, checking that it is a valid game
+    pub fn read_save(reader: &mut SaveReader) -> Result<GameState, LoadError> {
+        let seed: u64 = reader.parse("seed")?;
+        let shuffles: u64 = reader.parse("shuffles")?;
+        let rules: RuleSet = reader.rules("rules")?;
+        let turn_order: TurnOrder = reader.turn_order("turn-order")?;
+        let (current_line, current_player) = reader.field("current-player")?;
+        let turn: usize = reader.parse("turn")?;
+        let (winner_line, winner) = reader.field("winner")?;
+        let (pending_line, pending) = reader.field("pending")?;
+        let pending: Option<Pending> = match pending_from_code(pending) {
+            Some(x) => x,
+            None => return Err(reader.invalid(pending_line, "unknown pending decision")),
+        };
+        let uno_called: bool = reader.parse("uno-called")?;
+        let (vulnerable_line, uno_vulnerable) = reader.field("uno-vulnerable")?;
+        let wild_plus4_bluffed: bool = reader.parse("bluffed")?;
+        let draw_deck: Vec<Card> = reader.cards("draw-deck")?;
+        let play_deck: Vec<Card> = reader.cards("play-deck")?;
+        let (hands_line, num_players) = reader.field("hands")?;
+        let num_players: usize = num_players.parse().map_err(|_| reader.invalid(hands_line, "not a number"))?;
+        if num_players == 0 || num_players > MAX_PLAYERS {
+            return Err(reader.invalid(hands_line, &format!("there must be between 1 and {} players", MAX_PLAYERS)));
+        }
+        let mut players: Players = Players::new(num_players);
+        for i in 0..num_players {
+            players.players[i] = reader.cards("hand")?;
+        }
+
+        let parse_player = |line: usize, value: &str| -> Result<Option<usize>, LoadError> {
+            match value.parse::<usize>() {
+                Ok(player) if player < num_players => Ok(Some(player)),
+                _ if value == "none" => Ok(None),
+                _ => Err(LoadError::Invalid(line, format!("\"{}\" is not a player", value))),
+            }
+        };
+        players.current_player = match parse_player(current_line, current_player)? {
+            Some(player) => player,
+            None => return Err(reader.invalid(current_line, "there must be a current player")),
+        };
+        let winner: Option<usize> = parse_player(winner_line, winner)?;
+        let uno_vulnerable: Option<usize> = parse_player(vulnerable_line, uno_vulnerable)?;
+        let is_player = |x: usize| x < num_players;
+        let pending_valid: bool = match pending {
+            Some(Pending::DrawnCard(index)) => index < players.get_current_player().len(),
+            Some(Pending::Challenge(offender)) => is_player(offender),
+            _ => true,
+        };
+        if !pending_valid {
+            return Err(reader.invalid(pending_line, "pending decision refers to a missing card or player"));
+        }
+        if play_deck.is_empty() {
+            return Err(reader.invalid(hands_line, "the play deck must have a top card"));
+        }
+
+        // Every card of exactly one deck must be somewhere
+        let mut cards: Vec<String> = draw_deck.iter().chain(play_deck.iter())
+            .chain(players.players.iter().flat_map(|hand| hand.iter()))
+            .map(|card| card.code().split(':').next().unwrap().to_owned())
+            .collect();
+        let mut deck: Vec<String> = get_deck().iter().map(Card::code).collect();
+        cards.sort();
+        deck.sort();
+        if cards != deck {
+            return Err(reader.invalid(hands_line, "the cards in the game do not make up one deck"));
+        }
+
+        Ok(GameState {
+            turn_order: turn_order,
+            players: players,
+            draw_deck: draw_deck,
+            play_deck: play_deck,
+            winner: winner,
+            pending: pending,
+            uno_called: uno_called,
+            uno_vulnerable: uno_vulnerable,
+            turn: turn,
+            wild_plus4_bluffed: wild_plus4_bluffed,
+            events: Vec::new(),
+            rules: rules,
+            seed: seed,
+            shuffles: shuffles,
+        })
+    }
+}
+
+#[cfg(test)]
+impl GameState {
+    /// Constructs a GameState with the given hands and top card, for testing
+    /// The draw deck is a full shuffled deck
+    pub fn with_hands(hands: Vec<Vec<Card>>, top_card: Card, rules: RuleSet) -> GameState {
+        let mut game_state = GameState {
+            turn_order: TurnOrder::Normal,
+            play_deck: vec!(top_card),
+            draw_deck: get_deck(),
+            players: Players::new(hands.len()),
+            winner: None,
+            pending: None,
+            uno_called: false,
+            uno_vulnerable: None,
+            turn: 0,
+            wild_plus4_bluffed: false,
+            events: Vec::new(),
+            rules: rules,
+            seed: 0,
+            shuffles: 0,
+        };
+        game_state.players.players = hands;
+        game_state.shuffle();
+        game_state
+    }
+
+    /// Constructs a GameState whose starting card is the first of `starting_cards`,
+    /// with the rest of them next in the draw deck, for testing
+    pub fn with_starting_cards(num_players: usize, starting_cards: Vec<Card>, rules: RuleSet) -> GameState {
+        let mut game_state = GameState::with_hands(vec!(Vec::new(); num_players), Card::new(), rules);
+        game_state.play_deck.clear();
+        for i in 0..num_players {
+            for _ in 0..STARTING_HAND_SIZE {
+                let card: Card = game_state.try_pop_draw_deck().expect("Expected a card left to deal");
+                game_state.players.get_hand(i).push(card);
+            }
+        }
+        game_state.draw_deck.extend(starting_cards.into_iter().rev());
+        game_state.deal_first_play_card().expect("Expected a starting card");
+        game_state
     }
 }
 
@@ -153,6 +857,19 @@ pub enum TurnOrder {
     Reverse,
 }
 
+/// Returns the number a seat is shown as. Seats are indexed from 0, but people count them from 1
+pub fn seat_label(seat: usize) -> usize {
+    seat + 1
+}
+
+/// Reads a seat number typed by a person, counting from 1, as the seat's index
+pub fn parse_seat(text: &str) -> Option<usize> {
+    match text.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Some(n - 1),
+        _ => None,
+    }
+}
+
 /// Holds each player's hand and the current player's index
 /// Note that at least one player should exist
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -184,32 +901,252 @@ impl Players {
         self.players.get_mut(self.current_player).unwrap()
     }
 
-    /// Get the nth card from the current player's hand
-    pub fn get_from_current_player(&self, index: usize) -> Option<&Card> {
-        self.get_current_player().get(index)
+    /// Returns the number of players
+    pub fn len(&self) -> usize {
+        self.players.len()
     }
 
-        /// Get the nth card from the current player's hand
-    pub fn get_from_current_player_mut(&mut self, index: usize) -> Option<&mut Card> {
-        self.get_current_player_mut().get_mut(index)
+    /// Get the nth player's hand
+    pub fn get_player(&self, index: usize) -> &Vec<Card> {
+        &self.players[index]
     }
 
     fn next_player(&mut self) {
         self.current_player = (self.current_player + 1) % self.players.len()
     }
 
+    /// Passes every hand to the next player in the given direction
+    fn rotate_hands(&mut self, forward: bool) {
+        if forward {
+            self.players.rotate_right(1);
+        } else {
+            self.players.rotate_left(1);
+        }
+    }
+
     fn previous_player(&mut self) {
         if self.current_player == 0 {
             self.current_player = self.players.len() - 1;
         } else {
-            self.current_player = self.current_player - 1;
+            self.current_player -= 1;
         }
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Action {
     Play(Card, usize),
     Draw,
+    /// Keep a card drawn under `play_drawn_card` and end the turn
+    Pass,
+    /// Challenge a WildPlus4 (true) or accept it and draw 4 (false)
+    Challenge(bool),
+    /// Choose the color of a Wild turned over as the starting card
+    ChooseColor(Color),
+    /// Swap hands with the given player after playing a 7 under `seven_zero`
+    SwapHands(usize),
+    /// The given player plays the card at the given index out of turn under `jump_in`
+    JumpIn(usize, usize),
+    /// The current player calls UNO before playing their second to last card
+    CallUno,
+    /// The first player catches the second for not calling UNO, making them draw 2
+    Catch(usize, usize),
+}
+
+impl Action {
+    /// Returns a short text code for this action, such as `play 2 wild:r`, `draw` or `catch 0 1`.
+    /// Players and card indices are 0-indexed
+    pub fn code(&self) -> String {
+        use self::Action::*;
+        match *self {
+            Play(card, index) => format!("play {} {}", index, card.code()),
+            Draw => "draw".to_owned(),
+            Pass => "pass".to_owned(),
+            Challenge(challenge) => format!("challenge {}", challenge),
+            ChooseColor(color) => format!("color {}", color.code()),
+            SwapHands(player) => format!("swap {}", player),
+            JumpIn(player, index) => format!("jump-in {} {}", player, index),
+            CallUno => "uno".to_owned(),
+            Catch(catcher, player) => format!("catch {} {}", catcher, player),
+        }
+    }
+
+    /// Parses a code made by `Action::code`
+    pub fn from_code(code: &str) -> Result<Action, &'static str> {
+        let words: Vec<&str> = code.split_whitespace().collect();
+        let number = |i: usize| -> Result<usize, &'static str> {
+            words.get(i).and_then(|x| x.parse::<usize>().ok()).ok_or("Action is missing a number")
+        };
+        let action: Action = match words.first().cloned() {
+            Some("play") => match words.get(2) {
+                Some(card) => Action::Play(Card::from_code(card)?, number(1)?),
+                None => return Err("Action is missing a card"),
+            },
+            Some("draw") => Action::Draw,
+            Some("pass") => Action::Pass,
+            Some("challenge") => match words.get(1).map(|x| x.parse::<bool>()) {
+                Some(Ok(challenge)) => Action::Challenge(challenge),
+                _ => return Err("Challenge must be true or false"),
+            },
+            Some("color") => match words.get(1).and_then(|x| Color::from_code(x)) {
+                Some(Color::Any) | None => return Err("Action has an invalid color"),
+                Some(color) => Action::ChooseColor(color),
+            },
+            Some("swap") => Action::SwapHands(number(1)?),
+            Some("jump-in") => Action::JumpIn(number(1)?, number(2)?),
+            Some("uno") => Action::CallUno,
+            Some("catch") => Action::Catch(number(1)?, number(2)?),
+            _ => return Err("Unknown action"),
+        };
+        let expected_words: usize = match action {
+            Action::Draw | Action::Pass | Action::CallUno => 1,
+            Action::Play(_, _) | Action::JumpIn(_, _) | Action::Catch(_, _) => 3,
+            _ => 2,
+        };
+        if words.len() != expected_words {
+            return Err("Action has the wrong number of words");
+        }
+        Ok(action)
+    }
+}
+
+/// A decision the current player must make before play moves on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pending {
+    /// The card at this index was just drawn and may be played, or the player may pass
+    DrawnCard(usize),
+    /// The player must stack another draw card or draw this many cards
+    DrawPenalty(usize),
+    /// The player played a 7 and must choose whom to swap hands with
+    SwapHands,
+    /// The starting card is a Wild and the first player must choose its color
+    ChooseColor,
+    /// The player was hit by a WildPlus4 from this player and may challenge it
+    Challenge(usize),
+}
+
+/// The result of a successfully applied action
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Continue,
+    /// The given player has emptied their hand
+    Won(usize),
+}
+
+/// The reasons an action may be rejected by `GameState::apply`, or a game may not be dealt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    /// The card is not at the given index of the current player's hand
+    NotInHand,
+    /// The card cannot be played onto the top card
+    NotPlayable,
+    /// A wild card was played without choosing a color
+    WildColorMissing,
+    /// The action cannot be taken right now
+    NotAllowed,
+    /// The player has a playable card and may not draw or pass
+    MustPlay,
+    /// There is no player with that index
+    NoSuchPlayer,
+    /// Someone has already won the game
+    GameOver,
+    /// A game cannot be dealt to that many players
+    PlayerCount,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::RuleError::*;
+        match *self {
+            NotInHand => write!(f, "Card is not in your hand"),
+            NotPlayable => write!(f, "Card cannot be played onto the top card"),
+            WildColorMissing => write!(f, "Wild cards need a color"),
+            NotAllowed => write!(f, "That cannot be done right now"),
+            MustPlay => write!(f, "You must play a card"),
+            NoSuchPlayer => write!(f, "There is no such player"),
+            GameOver => write!(f, "The game is already over"),
+            PlayerCount => write!(f, "A game needs between 1 and {} players", MAX_PLAYERS),
+        }
+    }
+}
+
+/// Returns the code for a pending decision used in save files, such as `none` or `draw-penalty 4`
+pub fn pending_code(pending: Option<Pending>) -> String {
+    match pending {
+        None => "none".to_owned(),
+        Some(Pending::DrawnCard(index)) => format!("drawn-card {}", index),
+        Some(Pending::DrawPenalty(n)) => format!("draw-penalty {}", n),
+        Some(Pending::SwapHands) => "swap-hands".to_owned(),
+        Some(Pending::ChooseColor) => "choose-color".to_owned(),
+        Some(Pending::Challenge(offender)) => format!("challenge {}", offender),
+    }
+}
+
+/// Parses a code made by `pending_code`
+pub fn pending_from_code(code: &str) -> Option<Option<Pending>> {
+    let mut words = code.split_whitespace();
+    let kind: Option<&str> = words.next();
+    let number: Option<usize> = words.next().and_then(|x| x.parse().ok());
+    match (kind, number) {
+        (Some("none"), None) => Some(None),
+        (Some("drawn-card"), Some(n)) => Some(Some(Pending::DrawnCard(n))),
+        (Some("draw-penalty"), Some(n)) => Some(Some(Pending::DrawPenalty(n))),
+        (Some("swap-hands"), None) => Some(Some(Pending::SwapHands)),
+        (Some("choose-color"), None) => Some(Some(Pending::ChooseColor)),
+        (Some("challenge"), Some(n)) => Some(Some(Pending::Challenge(n))),
+        _ => None,
+    }
+}
+
+/// Returns the color that must be matched to play onto `card`
+pub fn active_color(card: &Card) -> Color {
+    match chosen_color(card) {
+        Some(x) => x,
+        None => card.color,
+    }
+}
+
+/// Returns the color chosen for a wild card, or None if the card is not wild
+fn chosen_color(card: &Card) -> Option<Color> {
+    use cards::CardType::*;
+    match card.card_type {
+        Wild(x) | WildPlus4(x) => Some(x),
+        _ => None,
+    }
+}
+
+/// Returns true if both cards have the same face, ignoring any color chosen for a wild card
+fn same_face(a: &Card, b: &Card) -> bool {
+    use cards::CardType::*;
+    match (a.card_type, b.card_type) {
+        (Wild(_), Wild(_)) | (WildPlus4(_), WildPlus4(_)) => true,
+        _ => a == b,
+    }
+}
+
+/// Adds an action for playing `card`, or one for each color if it is a wild card
+fn push_plays(actions: &mut Vec<Action>, card: &Card, index: usize) {
+    use cards::CardType::*;
+    match card.card_type {
+        Wild(_) | WildPlus4(_) => {
+            for color in WILD_COLORS.iter() {
+                let mut card: Card = *card;
+                card.card_type = match card.card_type {
+                    Wild(_) => Wild(*color),
+                    _ => WildPlus4(*color),
+                };
+                actions.push(Action::Play(card, index));
+            }
+        },
+        _ => actions.push(Action::Play(*card, index)),
+    }
+}
+
+/// Returns true if `card` may be stacked onto a draw card under the `stacking` rule.
+/// A Plus2 may go onto a Plus2, and a WildPlus4 onto either
+pub fn stackable_card(card: &Card, onto: &Card) -> bool {
+    use cards::CardType::*;
+    matches!((card.card_type, onto.card_type), (Plus2, Plus2) | (WildPlus4(_), Plus2) | (WildPlus4(_), WildPlus4(_)))
 }
 
 /// Returns true if at least one of the following is true:
@@ -219,9 +1156,7 @@ pub enum Action {
 pub fn playable_card(card: &Card, onto: &Card) -> bool {
     use cards::Color::*;
     use cards::CardType::*;
-    if card.color == Any {
-        return true
-    } else if card.color == onto.color || card.card_type == onto.card_type {
+    if card.color == Any || card.color == onto.color || card.card_type == onto.card_type {
         return true
     } else {
         match onto.card_type {
@@ -233,12 +1168,24 @@ pub fn playable_card(card: &Card, onto: &Card) -> bool {
     }
 }
 
-/// Returns a Vector of 108 cards containing:
+/// Returns the card as it is before being played, with no color chosen for a wild card
+fn without_chosen_color(card: &Card) -> Card {
+    use cards::CardType::*;
+    let mut card: Card = *card;
+    match card.card_type {
+        Wild(_) => card.card_type = Wild(Color::Any),
+        WildPlus4(_) => card.card_type = WildPlus4(Color::Any),
+        _ => {},
+    }
+    card
+}
+
+/// Returns every card in one deck, in order, as a Vector of 108 cards containing:
 /// 4 `WildPlus4` cards
 /// 4 `Wild` cards
 /// 2 of each color for Numbers 1 through 9, Reverse, Skip, and Plus2
 /// 1 of each color for Number 0
-fn get_deck() -> Vec<Card> {
+pub fn get_deck() -> Vec<Card> {
     let mut vec: Vec<Card> = Vec::new();
     let mut iter: Card = Card::new();
     loop {
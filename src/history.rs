@@ -0,0 +1,79 @@
+use game_state::{Action, GameState, Outcome, RuleError};
+
+/// Remembers the actions applied to a game so they can be undone and redone.
+/// Each action is stored with a snapshot of the game from before it was applied,
+/// and redoing an action applies it again, which gives the same result because
+/// every shuffle is determined by the game's seed.
+/// Each snapshot also holds the length of the round's log from before the action,
+/// so that undoing an action can take what it logged back out too
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct History {
+    enabled: bool,
+    done: Vec<(GameState, Action, usize)>,
+    undone: Vec<Action>,
+}
+
+impl History {
+    /// Constructs an empty History. A disabled History remembers nothing, so nothing can be undone
+    pub fn new(enabled: bool) -> History {
+        History {
+            enabled: enabled,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Applies an action to the game, remembering it if it succeeds. `log_len` is the length of the log before it.
+    /// Applying a new action forgets every action that could have been redone
+    pub fn apply(&mut self, game_state: &mut GameState, action: Action, log_len: usize) -> Result<Outcome, RuleError> {
+        if !self.enabled {
+            return game_state.apply(action);
+        }
+        let before: GameState = game_state.clone();
+        let result: Result<Outcome, RuleError> = game_state.apply(action);
+        if result.is_ok() {
+            self.done.push((before, action, log_len));
+            self.undone.clear();
+        }
+        result
+    }
+
+    /// Puts the game back how it was before the last action, and returns that action
+    /// along with the length the log should be cut back to
+    pub fn undo(&mut self, game_state: &mut GameState) -> Option<(Action, usize)> {
+        let (before, action, log_len) = self.done.pop()?;
+        *game_state = before;
+        self.undone.push(action);
+        Some((action, log_len))
+    }
+
+    /// Applies the last undone action again, and returns it along with its outcome.
+    /// `log_len` is the length of the log before it, which its events are logged after again
+    pub fn redo(&mut self, game_state: &mut GameState, log_len: usize) -> Option<(Action, Outcome)> {
+        let action: Action = self.undone.pop()?;
+        let before: GameState = game_state.clone();
+        let outcome: Outcome = game_state.apply(action).expect("Redone actions were applied before");
+        self.done.push((before, action, log_len));
+        Some((action, outcome))
+    }
+
+    #[cfg(test)]
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    #[cfg(test)]
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// Forgets every action, such as when a new round is dealt or a game is loaded
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
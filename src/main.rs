@@ -1,61 +1,532 @@
+// The code keeps its explicit returns and `field: field` initializers, and its tests live in `tests::tests`
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::module_inception)]
+
+mod bots;
 mod cards;
+mod client;
 mod tests;
 mod game_state;
+mod events;
+mod external;
+mod game_match;
+mod history;
+mod mcts;
+mod options;
+mod player;
+mod player_view;
 mod colorize;
 mod read_stdin;
+mod replay;
+mod rules;
+mod save;
+mod server;
+mod simulate;
+mod tui;
 
+use bots::*;
+use client::*;
+use external::*;
+use game_match::*;
 use game_state::*;
+use history::*;
+use mcts::DEFAULT_ITERATIONS;
+use options::*;
+use player::*;
+use player_view::PlayerView;
 use read_stdin::*;
+use replay::*;
+use rules::*;
+use server::*;
+use simulate::*;
+use std::cell::RefCell;
+use std::io::IsTerminal;
+use std::rc::Rc;
+use tui::*;
 
 
 fn main() {
-	let mut num_players: usize = 4;
-	println!("Welcome to Uno!");
-	println!("Type \"start\" to play. Type a number to set number of players (Currently {})", num_players);
+	let args: Vec<String> = std::env::args().collect();
+	if args.len() == 3 && args[1] == "--replay" {
+		view_replay(&args[2]);
+		return;
+	}
+	if args.len() >= 2 && args[1] == "simulate" {
+		run_simulation(&args[2..]);
+		return;
+	}
+	if args.len() >= 2 && args[1] == "serve" {
+		run_server(&args[2..]);
+		return;
+	}
+	if args.len() >= 2 && args[1] == "connect" {
+		run_client(&args[2..]);
+		return;
+	}
+	if args.len() >= 2 && args[1] == "watch" {
+		run_spectator(&args[2..]);
+		return;
+	}
+
+	if args.len() == 2 && (args[1] == "--help" || args[1] == "-h") {
+		println!("{}", GAME_USAGE);
+		println!("Without options, the game is set up from a menu");
+		return;
+	}
+
+	let mut options: GameOptions = match parse_game_args(&args[1..]) {
+		Ok(x) => x,
+		Err(err) => {
+			eprintln!("{}", err);
+			eprintln!("{}", GAME_USAGE);
+			return;
+		},
+	};
+	colorize::set_color(options.color);
+	let mut loaded: Option<Match> = None;
+	if let Some(ref path) = options.load {
+		match Match::load_from_file(path) {
+			Ok(x) => loaded = Some(x),
+			Err(err) => {
+				println!("{}", err);
+				return;
+			},
+		}
+	}
+	if !options.start && loaded.is_none() {
+		print_menu(&options);
+	}
 	// Preamble/Options
-	loop {
-		let menu_nav: String = read_string_from_stdin(None).to_lowercase();
+	while !options.start && loaded.is_none() {
+		let input: String = read_string_from_stdin(None);
+		let menu_nav: String = input.to_lowercase();
 		if menu_nav == "start" {
 			break;
+		} else if let Some(bot) = menu_nav.strip_prefix("bot ") {
+			let mut words = bot.split_whitespace();
+			let seat: Option<usize> = words.next().and_then(parse_seat);
+			let kind: Option<Option<Bot>> = match words.next() {
+				// The command keeps its case
+				Some("run") => Some(input.splitn(4, ' ').nth(3).map(|command| Bot::External(command.trim().to_owned()))),
+				Some(name) => Some(Difficulty::from_name(name).map(Bot::BuiltIn)),
+				None => None,
+			};
+			match (seat, kind) {
+				(_, Some(None)) => println!("Difficulty must be one of {}, or \"run\" and a command", DIFFICULTY_NAMES.join(", ")),
+				(Some(seat), kind) => {
+					let existing: Option<usize> = options.bot_seats.iter().position(|(x, _)| *x == seat);
+					match (existing, kind) {
+						(Some(i), None) => {
+							options.bot_seats.remove(i);
+							println!("Seat {} is now played by a person", seat_label(seat));
+						},
+						(existing, kind) => {
+							let bot: Bot = kind.and_then(|x| x).unwrap_or(Bot::BuiltIn(Difficulty::Medium));
+							if let Some(i) = existing {
+								options.bot_seats.remove(i);
+							}
+							println!("Seat {} is now played by {}", seat_label(seat), bot);
+							options.bot_seats.push((seat, bot));
+						},
+					}
+				},
+				_ => println!("Seat does not exist!"),
+			}
+		} else if menu_nav == "watch" || menu_nav == "watch hands" {
+			let show_hands: bool = menu_nav == "watch hands";
+			options.spectate = if options.spectate == Some(show_hands) { None } else { Some(show_hands) };
+			match options.spectate {
+				Some(true) => println!("Watching is now on, with every hand shown"),
+				Some(false) => println!("Watching is now on"),
+				None => println!("Watching is now off"),
+			}
+		} else if let Some(name) = menu_nav.strip_prefix("preset ") {
+			match RuleSet::preset(name.trim()) {
+				Some(x) => {
+					options.rules = x;
+					println!("Rules are now {}", options.rules);
+				},
+				None => println!("Preset must be one of {}", PRESET_NAMES.join(", ")),
+			}
+		} else if menu_nav == "color" {
+			options.color = !options.color;
+			colorize::set_color(options.color);
+			println!("Color is now {}", if options.color { "on" } else { "off" });
+		} else if menu_nav == "privacy" {
+			options.privacy = !options.privacy;
+			println!("Privacy is now {}", if options.privacy { "on" } else { "off" });
+		} else if menu_nav == "tui" {
+			options.full_screen = !options.full_screen;
+			println!("Full-screen interface is now {}", if options.full_screen { "on" } else { "off" });
+		} else if menu_nav == "undo" {
+			options.undo_allowed = !options.undo_allowed;
+			println!("Undo is now {}", if options.undo_allowed { "on" } else { "off" });
 		} else if let Ok(n) = menu_nav.parse::<i32>() {
-			if n <= 0 {
-				println!("Must have at least one player");
+			if n <= 0 || n as usize > MAX_PLAYERS {
+				println!("There must be between 1 and {} players", MAX_PLAYERS);
 			}
 			else {
-				num_players = n as usize;
-				println!("Number of players is now {}", num_players);
+				options.num_players = n as usize;
+				println!("Number of players is now {}", options.num_players);
 			}
+		} else if let Some(target) = menu_nav.strip_prefix("target ") {
+			match target.trim().parse::<u32>() {
+				Ok(n) if n > 0 => {
+					options.target_score = n;
+					println!("Target score is now {}", options.target_score);
+				},
+				_ => println!("Target score must be a positive number"),
+			}
+		} else if let Some(time) = menu_nav.strip_prefix("expert ") {
+			match time.trim().parse::<u64>() {
+				Ok(n) => {
+					options.set_expert_time(n);
+					println!("Expert time is now {}", expert_time_text(&options));
+				},
+				_ => println!("Expert time must be a number of milliseconds"),
+			}
+		} else if menu_nav.starts_with("load ") {
+			// The path keeps its case
+			let path: &str = input.split_once(' ').map_or("", |(_, path)| path);
+			match Match::load_from_file(path.trim()) {
+				Ok(x) => {
+					loaded = Some(x);
+					break;
+				},
+				Err(err) => println!("{}", err),
+			}
+		} else if let Some(on) = options.rules.toggle(&menu_nav) {
+			println!("{} is now {}", menu_nav, if on { "on" } else { "off" });
 		} else {
 			println!("Command not recoginized");
 		}
 	}
 
-	println!("Uno game start! (Number of players: {})", num_players);
-	let mut game_state: GameState = GameState::new(num_players);
+	// A round loaded partway through cannot be replayed from the deal
+	let (mut uno_match, mut replay): (Match, Option<Replay>) = match loaded {
+		Some(x) => (x, None),
+		None => {
+			let dealt: Result<Match, RuleError> = match options.seed {
+				Some(seed) => Match::with_seed(options.num_players, seed, options.rules, options.target_score),
+				None => Match::new(options.num_players, options.rules, options.target_score),
+			};
+			let x: Match = match dealt {
+				Ok(x) => x,
+				Err(err) => {
+					println!("{}", err);
+					return;
+				},
+			};
+			let replay: Replay = x.start_replay();
+			(x, Some(replay))
+		},
+	};
+
+	let mut history: History = History::new(options.undo_allowed);
+	// Piped input keeps the line-based interface, which scripts rely on
+	let output: Output = if options.full_screen && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+		match Screen::start() {
+			Ok(screen) => Output::Screen(Rc::new(RefCell::new(screen))),
+			Err(err) => {
+				println!("Could not start the full-screen interface: {}", err);
+				Output::Lines
+			},
+		}
+	} else {
+		if options.full_screen {
+			println!("The full-screen interface needs a terminal");
+		}
+		Output::Lines
+	};
+	// A seeded match is played the same way every time
+	let bot_seed = |seat: usize| -> u64 { options.seed.map_or_else(rand::random, |seed| seed.wrapping_add(seat as u64 + 1)) };
+	let mut seats: Vec<Box<dyn Player>> = (0..uno_match.round.players.len())
+		.map(|seat| -> Box<dyn Player> {
+			match options.bot_seats.iter().find(|(x, _)| *x == seat) {
+				Some((_, Bot::BuiltIn(difficulty))) => difficulty.bot_with_budget(bot_seed(seat), options.expert_budget()),
+				Some((_, Bot::External(command))) => match ExternalPlayer::start(command, DEFAULT_TIMEOUT, output.clone()) {
+					Ok(bot) => {
+						output.say(&format!("Seat {} is played by {}", seat_label(seat), bot.name()));
+						Box::new(bot)
+					},
+					Err(err) => {
+						output.say(&format!("{}. Seat {} is played by the computer ({}) instead", err, seat_label(seat), Difficulty::Medium));
+						Difficulty::Medium.bot(bot_seed(seat))
+					},
+				},
+				None => match output {
+					Output::Lines => Box::new(StdinPlayer),
+					Output::Screen(ref screen) => Box::new(TuiPlayer::new(screen.clone())),
+				},
+			}
+		})
+		.collect();
+	let humans: Vec<bool> = (0..seats.len()).map(|seat| options.bot_seats.iter().all(|(x, _)| *x != seat)).collect();
+	// Piped input has nobody to hide the hand from
+	let privacy: bool = options.privacy && std::io::stdin().is_terminal();
 
-	// Main game loop
+	// Main match loop, one round at a time
 	loop {
-		colorize::print_game_state(&game_state);
-		// Get card to play
-		let action: Action = read_action_from_stdin(&mut game_state.players);
-		match action {
-			Action::Play(mut card, index) => {
-				if playable_card(&card, game_state.top_card()) {
-					game_state.players.get_current_player_mut().remove(index);
-					if game_state.players.get_current_player().len() == 0 {
+		output.say(&format!("Uno round {} start! (Number of players: {})", uno_match.round_number() + 1, uno_match.round.players.len()));
+		if !play_round(&mut uno_match, &mut seats, &humans, options.spectate, privacy, &mut replay, &mut history, &output) {
+			return;
+		}
+
+		let winner: usize = uno_match.round.winner().unwrap();
+		let points: u32 = uno_match.score_round();
+		output.say(&format!("Player {} scores {} points", seat_label(winner), points));
+		for (i, score) in uno_match.scores().iter().enumerate() {
+			output.say(&format!("Player {}: {}/{}", seat_label(i), score, uno_match.target_score()));
+		}
+		if let Some(ref replay) = replay {
+			let path: String = format!("uno-replay-{}.txt", uno_match.round.seed());
+			match replay.save_to_file(&path) {
+				Ok(()) => output.say(&format!("Replay saved to {}", path)),
+				Err(err) => output.say(&err.to_string()),
+			}
+		}
+
+		if let Some(winner) = uno_match.winner() {
+			output.say(&format!("Player {} wins the match!", seat_label(winner)));
+			if let Output::Screen(ref screen) = output {
+				screen.borrow_mut().finish();
+				// The screen's log is gone once the terminal is put back
+				println!("Player {} wins the match!", seat_label(winner));
+			}
+			break;
+		}
+		uno_match.next_round();
+		replay = Some(uno_match.start_replay());
+		history.clear();
+	}
+}
+
+/// Describes how long the expert bot thinks each move, for the start menu
+fn expert_time_text(options: &GameOptions) -> String {
+	match options.expert_time {
+		Some(time) => format!("{} ms", time.as_millis()),
+		None => format!("{} searches", DEFAULT_ITERATIONS),
+	}
+}
+
+/// Prints the start menu's commands and the current options
+fn print_menu(options: &GameOptions) {
+	println!("Welcome to Uno!");
+	println!("Type \"start\" to play. Type a number to set number of players (Currently {})", options.num_players);
+	println!("Type \"target\" and a number to set the score needed to win (Currently {})", options.target_score);
+	println!("Type the name of a house rule to turn it on or off ({})", options.rules);
+	println!("Type \"preset\" and {} to start over from a set of rules", PRESET_NAMES.join("/"));
+	println!("Type \"bot\", a seat number and optionally a difficulty ({}) to have the computer play that seat", DIFFICULTY_NAMES.join("/"));
+	println!("Type \"bot\", a seat number, \"run\" and a command to have a bot program play that seat");
+	println!("Type \"bot\" and the seat number again to play it yourself");
+	println!("Type \"expert\" and a number of milliseconds to let the expert bot think that long each move, or 0 for a fixed amount (Currently {})", expert_time_text(options));
+	println!("Type \"undo\" to turn taking back moves on or off (Currently {})", if options.undo_allowed { "on" } else { "off" });
+	println!("Type \"watch\" to see the table before each of the computer's turns, or \"watch hands\" to see every hand too");
+	println!("Type \"privacy\" to hide each hand between turns when more than one person plays (Currently {})", if options.privacy { "on" } else { "off" });
+	println!("Type \"color\" to turn colored cards on or off (Currently {})", if options.color { "on" } else { "off" });
+	println!("Type \"tui\" to play in a full-screen interface with the arrow keys (Currently {})", if options.full_screen { "on" } else { "off" });
+	println!("Type \"load\" and a file name to resume a saved game. Type \"save\" and a file name during a game to save it");
+	println!("A replay of each round is saved when it ends. Run with \"--replay\" and a file name to step through one");
+	println!("To play over a network, run \"uno serve\" to host and \"uno connect\" with its address and your name to join");
+	println!("Run \"uno watch\" with the same to spectate a networked game");
+	println!("Run \"uno --help\" to see how to start a game without this menu");
+}
+
+/// Plays a round until someone empties their hand, recording each action in the replay and history.
+/// Only the hands of seats played by people are printed, unless `spectate` is `Some(true)`.
+/// The full-screen interface draws the table itself.
+/// With `privacy`, each person's hand is hidden between their turns when several people share the terminal
+/// Returns false if a person quit before the round was over
+#[allow(clippy::too_many_arguments)]
+fn play_round(uno_match: &mut Match, seats: &mut [Box<dyn Player>], humans: &[bool], spectate: Option<bool>, privacy: bool,
+	replay: &mut Option<Replay>, history: &mut History, output: &Output) -> bool {
+	// Everything that has happened this round, for anyone who asks
+	let mut log: Vec<String> = Vec::new();
+	// The starting card may already have had an effect
+	for event in uno_match.round.drain_events() {
+		output.say(&event.to_string());
+		log.push(event.to_string());
+	}
+	let mut hand_off: HandOff = HandOff::new(privacy, humans);
+	// A loaded round may already be over
+	while uno_match.round.winner().is_none() {
+		let current: usize = uno_match.round.players.current_player;
+		let view: PlayerView = uno_match.round.view_for(current);
+		if humans[current] {
+			hand_off.before_turn(output, &view);
+		}
+		match (output, spectate) {
+			(Output::Screen(screen), _) => if !humans[current] {
+				// Keep showing the last hand a person played, or the first hand if nobody has
+				let viewer: usize = screen.borrow().viewer().unwrap_or(0);
+				screen.borrow_mut().show(uno_match.round.view_for(viewer));
+			},
+			(Output::Lines, _) if humans[current] => colorize::print_player_view(&view),
+			(Output::Lines, Some(show_hands)) => colorize::print_spectator_view(&uno_match.round.spectator_view(show_hands)),
+			(Output::Lines, None) => {},
+		}
+		let action: Action = match choose_command(seats[current].as_mut(), &view) {
+			Command::Act(action) => action,
+			Command::Save(path) => {
+				match uno_match.save_to_file(&path) {
+					Ok(()) => output.say(&format!("Saved to {}", path)),
+					Err(err) => output.say(&err.to_string()),
+				}
+				continue;
+			},
+			Command::Load(path) => {
+				match Match::load_from_file(&path) {
+					Ok(x) if x.round.players.len() == seats.len() => {
+						*uno_match = x;
+						*replay = None;
+						history.clear();
+						log.clear();
+						output.say(&format!("Loaded {}", path));
+					},
+					Ok(_) => output.say("Saved game has a different number of players"),
+					Err(err) => output.say(&err.to_string()),
+				}
+				continue;
+			},
+			Command::Undo => {
+				if !history.enabled() {
+					output.say("Undo is turned off");
+					continue;
+				}
+				// Take back the computer's moves too, back to a person's turn
+				let mut undone: usize = 0;
+				while let Some((_, log_len)) = history.undo(&mut uno_match.round) {
+					if let Some(ref mut replay) = *replay {
+						replay.undo();
+					}
+					log.truncate(log_len);
+					undone += 1;
+					if humans[uno_match.round.players.current_player] {
 						break;
 					}
-					game_state.play_card(&mut card);
 				}
-				else {
-					println!("Cannot play {} onto {}", card, game_state.top_card());
+				match undone {
+					0 => output.say("Nothing to undo"),
+					1 => output.say("Took back the last action"),
+					n => output.say(&format!("Took back the last {} actions", n)),
+				}
+				continue;
+			},
+			Command::Show(Info::History) => {
+				if log.is_empty() {
+					output.say("Nothing has happened yet this round");
+				}
+				for line in log.iter() {
+					output.say(line);
+				}
+				continue;
+			},
+			Command::Show(Info::Score) => {
+				for (i, score) in uno_match.scores().iter().enumerate() {
+					output.say(&format!("Player {}: {}/{}", seat_label(i), score, uno_match.target_score()));
+				}
+				continue;
+			},
+			Command::Quit => return false,
+			Command::Redo => {
+				match history.redo(&mut uno_match.round, log.len()) {
+					Some((action, outcome)) => {
+						if let Some(ref mut replay) = *replay {
+							replay.record(action);
+						}
+						print_outcome(uno_match, Ok(outcome), action, output, &mut log);
+					},
+					None => output.say("Nothing to redo"),
 				}
+				continue;
 			},
-			Action::Draw => {
-				game_state.draw_card()
+		};
+		let result: Result<Outcome, RuleError> = history.apply(&mut uno_match.round, action, log.len());
+		if let (Ok(_), &mut Some(ref mut replay)) = (result, &mut *replay) {
+			replay.record(action);
+		}
+		hand_off.after_action(output, uno_match.round.players.current_player, uno_match.round.winner().is_some());
+		print_outcome(uno_match, result, action, output, &mut log);
+
+		// The computer may jump in or catch someone out of turn
+		if result.is_ok() {
+			for seat in 0..seats.len() {
+				if humans[seat] || seat == uno_match.round.players.current_player || uno_match.round.winner().is_some() {
+					continue;
+				}
+				let reaction: Option<Action> = seats[seat].react(&uno_match.round.view_for(seat));
+				if let Some(action) = reaction {
+					let result: Result<Outcome, RuleError> = history.apply(&mut uno_match.round, action, log.len());
+					if let (Ok(_), &mut Some(ref mut replay)) = (result, &mut *replay) {
+						replay.record(action);
+					}
+					print_outcome(uno_match, result, action, output, &mut log);
+				}
 			}
 		}
 	}
+	true
+}
 
-	println!("You win player {}!", game_state.players.current_player + 1);
-}
\ No newline at end of file
+/// Prints the events from the last action, adding them to the round's log, and whether it won or was rejected
+fn print_outcome(uno_match: &mut Match, result: Result<Outcome, RuleError>, action: Action, output: &Output, log: &mut Vec<String>) {
+	let game_state: &mut GameState = &mut uno_match.round;
+	for event in game_state.drain_events() {
+		output.say(&event.to_string());
+		log.push(event.to_string());
+	}
+	match result {
+		Ok(Outcome::Won(player)) => output.say(&format!("You win player {}!", seat_label(player))),
+		Ok(Outcome::Continue) => {},
+		Err(RuleError::NotPlayable) => {
+			if let Action::Play(card, _) = action {
+				output.say(&format!("Cannot play {} onto {}", card, game_state.top_card()));
+			}
+		},
+		Err(err) => output.say(&err.to_string()),
+	}
+}
+
+/// Steps through a replay file, showing the game before each action
+fn view_replay(path: &str) {
+	let replay: Replay = match Replay::load_from_file(path) {
+		Ok(x) => x,
+		Err(err) => {
+			println!("{}", err);
+			return;
+		},
+	};
+	let states: Vec<GameState> = replay.states();
+	let last_step: usize = replay.actions().len();
+	let mut step: usize = 0;
+	loop {
+		println!("Step {}/{}", step, last_step);
+		// Show what the last action did
+		let game_state: &GameState = &states[step];
+		let seen: usize = if step == 0 { 0 } else { states[step - 1].events().len() };
+		for event in &game_state.events()[seen..] {
+			println!("{}", event);
+		}
+		colorize::print_game_state(game_state);
+		match replay.actions().get(step) {
+			Some(action) => println!("Next action: {}", action.code()),
+			None => println!("End of replay"),
+		}
+
+		loop {
+			let input: String = read_string_from_stdin(Some("n: next, b: back, a number: go to step, q: quit".to_owned()));
+			match input.as_ref() {
+				"n" | "" if step < last_step => step += 1,
+				"b" if step > 0 => step -= 1,
+				"q" => return,
+				_ => match input.parse::<usize>() {
+					Ok(n) if n <= last_step => step = n,
+					_ => {
+						println!("Cannot go there");
+						continue;
+					},
+				},
+			}
+			break;
+		}
+	}
+}
@@ -0,0 +1,244 @@
+extern crate rand;
+
+use bots::{Difficulty, HeuristicPlayer};
+use cards::{Card, Color};
+use game_state::{Action, GameState, WILD_COLORS, active_color};
+use player::Player;
+use player_view::PlayerView;
+use std::time::{Duration, Instant};
+use self::rand::{RngExt, SeedableRng};
+use self::rand::rngs::Xoshiro256PlusPlus;
+use self::rand::seq::IndexedRandom;
+
+/// How much weight the search gives to trying actions it has not tried much
+const EXPLORATION: f64 = 0.7;
+/// The most actions a random playout takes before the player with the fewest cards is called the winner
+const PLAYOUT_LIMIT: usize = 400;
+/// The number of searches per decision when playing as `Difficulty::Expert`
+pub const DEFAULT_ITERATIONS: usize = 1000;
+
+/// How much searching the bot may do for each decision.
+/// The search stops once either limit is reached
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub iterations: usize,
+    pub time: Option<Duration>,
+}
+
+impl Budget {
+    pub fn iterations(iterations: usize) -> Budget {
+        Budget {
+            iterations: iterations,
+            time: None,
+        }
+    }
+
+    /// Searches for `time` on each decision, however many iterations that takes
+    pub fn time(time: Duration) -> Budget {
+        Budget {
+            iterations: usize::MAX,
+            time: Some(time),
+        }
+    }
+}
+
+/// A node of the search tree, reached by taking an action
+struct Node {
+    /// The player who took the action that leads here
+    actor: usize,
+    visits: u32,
+    /// The number of times this node's action was legal when its parent was visited
+    availability: u32,
+    /// The total reward to `actor` from visits through this node
+    reward: f64,
+    children: Vec<(Action, Node)>,
+}
+
+impl Node {
+    fn new(actor: usize) -> Node {
+        Node {
+            actor: actor,
+            visits: 0,
+            availability: 0,
+            reward: 0.0,
+            children: Vec::new(),
+        }
+    }
+
+    /// The upper confidence bound used to choose which child to search
+    fn ucb(&self) -> f64 {
+        self.reward / self.visits as f64 + EXPLORATION * ((self.availability as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// Information set Monte Carlo tree search. Each search deals the cards the bot cannot see
+/// at random with `GameState::determinize`, keeping what the bot knows about which colors
+/// players lack, then plays the game out. The tree is shared between deals, so the bot
+/// picks actions that do well whatever the other players hold
+pub struct MctsPlayer {
+    budget: Budget,
+    rng: Xoshiro256PlusPlus,
+    /// Keeps track of other players, calls and catches UNO, and jumps in
+    tracker: HeuristicPlayer,
+}
+
+impl MctsPlayer {
+    pub fn new(budget: Budget, seed: u64) -> MctsPlayer {
+        MctsPlayer {
+            budget: budget,
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+            tracker: HeuristicPlayer::new(Difficulty::Hard),
+        }
+    }
+
+    /// Returns the legal action for the viewer that did best across the budgeted searches
+    pub fn search(&mut self, view: &PlayerView) -> Action {
+        if view.legal_actions.len() == 1 {
+            return view.legal_actions[0];
+        }
+        let start: Instant = Instant::now();
+        let mut root: Node = Node::new(view.player);
+        for _ in 0..self.budget.iterations {
+            if let Some(time) = self.budget.time {
+                if start.elapsed() >= time {
+                    break;
+                }
+            }
+            let seed: u64 = self.rng.random();
+            let mut game_state: GameState = GameState::determinize(view, self.tracker.known_lacks(), seed);
+            self.iterate(&mut root, &mut game_state);
+        }
+        root.children.iter()
+            .filter(|&(action, _)| view.legal_actions.contains(action))
+            .max_by_key(|&(_, child)| child.visits)
+            .map(|&(action, _)| action)
+            .unwrap_or(view.legal_actions[0])
+    }
+
+    /// Searches one step deeper from `node`, returning the reward to each player
+    fn iterate(&mut self, node: &mut Node, game_state: &mut GameState) -> Vec<f64> {
+        if game_state.winner().is_some() {
+            return rewards(game_state);
+        }
+        let player: usize = game_state.players.current_player;
+        let actions: Vec<Action> = game_state.legal_actions();
+        for (action, child) in node.children.iter_mut() {
+            if actions.contains(action) {
+                child.availability += 1;
+            }
+        }
+
+        let untried: Vec<Action> = actions.iter().cloned()
+            .filter(|action| node.children.iter().all(|(x, _)| x != action))
+            .collect();
+        let (index, result): (usize, Vec<f64>) = match untried.choose(&mut self.rng) {
+            // Add one new action to the tree, then play the rest of the game out at random
+            Some(&action) => {
+                game_state.apply(action).expect("Expected legal actions to apply");
+                let mut child: Node = Node::new(player);
+                child.availability = 1;
+                node.children.push((action, child));
+                (node.children.len() - 1, self.playout(game_state))
+            },
+            None => {
+                let mut best: Option<(f64, usize)> = None;
+                for (i, (action, child)) in node.children.iter().enumerate() {
+                    if !actions.contains(action) {
+                        continue;
+                    }
+                    let ucb: f64 = child.ucb();
+                    match best {
+                        Some((best_ucb, _)) if best_ucb >= ucb => {},
+                        _ => best = Some((ucb, i)),
+                    }
+                }
+                let i: usize = best.expect("Expected a legal action that has been tried").1;
+                let action: Action = node.children[i].0;
+                game_state.apply(action).expect("Expected legal actions to apply");
+                (i, self.iterate(&mut node.children[i].1, game_state))
+            },
+        };
+        let child: &mut Node = &mut node.children[index].1;
+        child.visits += 1;
+        child.reward += result[child.actor];
+        result
+    }
+
+    /// Plays the game out until someone wins or the limit is reached.
+    /// Players play a random card that is not wild if they can, then a wild card
+    /// of the color they hold the most, and otherwise take a random action such as drawing
+    fn playout(&mut self, game_state: &mut GameState) -> Vec<f64> {
+        for _ in 0..PLAYOUT_LIMIT {
+            if game_state.winner().is_some() {
+                break;
+            }
+            let actions: Vec<Action> = game_state.legal_actions();
+            let plain: Vec<Action> = actions.iter().cloned()
+                .filter(|action| match *action {
+                    Action::Play(card, _) => card.color != Color::Any,
+                    _ => false,
+                })
+                .collect();
+            let action: Action = match plain.choose(&mut self.rng) {
+                Some(&action) => action,
+                None => {
+                    let color: Color = most_held_color(game_state.players.get_current_player());
+                    let wild: Option<Action> = actions.iter().cloned().find(|action| match *action {
+                        Action::Play(card, _) => active_color(&card) == color,
+                        _ => false,
+                    });
+                    match wild {
+                        Some(action) => action,
+                        None => *actions.choose(&mut self.rng).expect("Expected a legal action"),
+                    }
+                },
+            };
+            game_state.apply(action).expect("Expected legal actions to apply");
+        }
+        rewards(game_state)
+    }
+}
+
+/// Returns the color of the most cards in the hand, ignoring wild cards
+fn most_held_color(hand: &[Card]) -> Color {
+    let mut best: (usize, Color) = (0, WILD_COLORS[0]);
+    for color in WILD_COLORS.iter() {
+        let held: usize = hand.iter().filter(|card| card.color == *color).count();
+        if held > best.0 {
+            best = (held, *color);
+        }
+    }
+    best.1
+}
+
+/// Returns 1 for the winner, or for the player with the fewest cards if nobody has won yet, and 0 for everyone else
+fn rewards(game_state: &GameState) -> Vec<f64> {
+    let num_players: usize = game_state.players.len();
+    let winner: usize = match game_state.winner() {
+        Some(x) => x,
+        None => (0..num_players).min_by_key(|player| game_state.players.get_player(*player).len()).unwrap(),
+    };
+    (0..num_players).map(|player| if player == winner { 1.0 } else { 0.0 }).collect()
+}
+
+impl Player for MctsPlayer {
+    fn choose_action(&mut self, view: &PlayerView) -> Action {
+        self.tracker.observe(view);
+        if let Some(action) = self.tracker.urgent_action(view) {
+            return action;
+        }
+        self.search(view)
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        self.tracker.observe(view);
+        match self.search(view) {
+            Action::ChooseColor(color) => color,
+            _ => self.tracker.choose_color(view),
+        }
+    }
+
+    fn react(&mut self, view: &PlayerView) -> Option<Action> {
+        self.tracker.react(view)
+    }
+}
@@ -0,0 +1,189 @@
+use bots::{DIFFICULTY_NAMES, Difficulty};
+use game_match::DEFAULT_TARGET_SCORE;
+use game_state::{MAX_PLAYERS, parse_seat, seat_label};
+use mcts::{Budget, DEFAULT_ITERATIONS};
+use rules::{PRESET_NAMES, RuleSet};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const GAME_USAGE: &str = "Usage: uno [--players N] [--seats human,medium,...] [--program SEAT COMMAND] [--preset official|house|party] \
+[--rules rule,...] [--expert-time MS] [--seed N] [--target N] [--color on|off] [--output lines|tui] [--no-undo] [--no-privacy] [--watch] [--watch-hands] [--load FILE] [--menu]";
+
+/// A bot chosen to play a seat
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Bot {
+    BuiltIn(Difficulty),
+    /// A bot program, run with this command line. See the `external` module
+    External(String),
+}
+
+impl fmt::Display for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Bot::BuiltIn(difficulty) => write!(f, "the computer ({})", difficulty),
+            Bot::External(ref command) => write!(f, "the bot program \"{}\"", command),
+        }
+    }
+}
+
+/// The options for a local game. They start from the command line and can be changed at the start menu
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameOptions {
+    pub num_players: usize,
+    /// 0-indexed seats played by the computer, and which bot plays them
+    pub bot_seats: Vec<(usize, Bot)>,
+    /// How long the Expert bot may search on each decision, or None for `DEFAULT_ITERATIONS` searches
+    pub expert_time: Option<Duration>,
+    pub rules: RuleSet,
+    pub target_score: u32,
+    /// The seed of the first round, or None for a random one
+    pub seed: Option<u64>,
+    pub undo_allowed: bool,
+    /// Whether each person's hand is hidden until they take the seat, when several people share the terminal
+    pub privacy: bool,
+    /// Whether turns taken by the computer are shown as a spectator would see them, and if so whether every hand is shown
+    pub spectate: Option<bool>,
+    /// Whether cards are printed in color. The full-screen interface is always in color
+    pub color: bool,
+    pub full_screen: bool,
+    /// A saved game to resume instead of starting a new one
+    pub load: Option<String>,
+    /// Whether the start menu is skipped
+    pub start: bool,
+}
+
+impl Default for GameOptions {
+    fn default() -> GameOptions {
+        GameOptions {
+            num_players: 4,
+            bot_seats: Vec::new(),
+            expert_time: None,
+            rules: RuleSet::official(),
+            target_score: DEFAULT_TARGET_SCORE,
+            seed: None,
+            undo_allowed: true,
+            privacy: true,
+            spectate: None,
+            color: true,
+            full_screen: false,
+            load: None,
+            start: false,
+        }
+    }
+}
+
+impl GameOptions {
+    /// Gives the Expert bot `milliseconds` to search on each decision, or a fixed number of searches if it is 0
+    pub fn set_expert_time(&mut self, milliseconds: u64) {
+        self.expert_time = if milliseconds == 0 { None } else { Some(Duration::from_millis(milliseconds)) };
+    }
+
+    /// Returns how much the Expert bot may search on each decision
+    pub fn expert_budget(&self) -> Budget {
+        self.expert_time.map_or(Budget::iterations(DEFAULT_ITERATIONS), Budget::time)
+    }
+}
+
+/// Reads the options given to `uno` when it is run without a subcommand.
+/// Giving any option starts the game right away, unless `--menu` is given too
+pub fn parse_game_args(args: &[String]) -> Result<GameOptions, String> {
+    let mut options: GameOptions = GameOptions {
+        start: !args.is_empty(),
+        ..GameOptions::default()
+    };
+    let mut players: Option<usize> = None;
+    let mut seats: Option<usize> = None;
+    // House rules are turned on after the preset, whichever order they are given in
+    let mut house_rules: Vec<&str> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--no-undo" => options.undo_allowed = false,
+            "--no-privacy" => options.privacy = false,
+            "--watch" => options.spectate = Some(false),
+            "--watch-hands" => options.spectate = Some(true),
+            "--menu" => options.start = false,
+            _ => {
+                let value: &String = match args.next() {
+                    Some(x) => x,
+                    None => return Err(format!("{} needs a value", arg)),
+                };
+                let on_off = || match value.as_ref() {
+                    "on" => Ok(true),
+                    "off" => Ok(false),
+                    _ => Err(format!("{} must be on or off", arg)),
+                };
+                match arg.as_ref() {
+                    "--players" => players = Some(parse_number(arg, value)?),
+                    "--target" => options.target_score = parse_number(arg, value)?,
+                    "--seed" => options.seed = Some(parse_number(arg, value)?),
+                    "--expert-time" => options.set_expert_time(parse_number(arg, value)?),
+                    "--color" => options.color = on_off()?,
+                    "--output" => options.full_screen = match value.as_ref() {
+                        "lines" => false,
+                        "tui" => true,
+                        _ => return Err("Output must be lines or tui".to_owned()),
+                    },
+                    "--load" => options.load = Some(value.clone()),
+                    "--seats" => {
+                        let mut count: usize = 0;
+                        for (seat, name) in value.split(',').enumerate() {
+                            options.bot_seats.retain(|(x, _)| *x != seat);
+                            match (name, Difficulty::from_name(name)) {
+                                ("human", _) => {},
+                                (_, Some(difficulty)) => options.bot_seats.push((seat, Bot::BuiltIn(difficulty))),
+                                (_, None) => return Err(format!("Seats must be human, {}", DIFFICULTY_NAMES.join(", "))),
+                            }
+                            count += 1;
+                        }
+                        seats = Some(count);
+                    },
+                    "--program" => {
+                        let seat: usize = match parse_seat(value) {
+                            Some(x) => x,
+                            None => return Err("--program needs a seat number".to_owned()),
+                        };
+                        let command: &String = match args.next() {
+                            Some(x) => x,
+                            None => return Err("--program needs a command".to_owned()),
+                        };
+                        options.bot_seats.retain(|(x, _)| *x != seat);
+                        options.bot_seats.push((seat, Bot::External(command.clone())));
+                    },
+                    "--preset" => match RuleSet::preset(value) {
+                        Some(x) => options.rules = x,
+                        None => return Err(format!("Preset must be one of {}", PRESET_NAMES.join(", "))),
+                    },
+                    "--rules" => house_rules.extend(value.split(',')),
+                    _ => return Err(format!("Unknown option {}", arg)),
+                }
+            },
+        }
+    }
+    for name in house_rules {
+        if options.rules.toggle(name) != Some(true) {
+            return Err(format!("Unknown or repeated rule \"{}\"", name));
+        }
+    }
+    options.num_players = match (players, seats) {
+        (Some(x), Some(y)) if x != y => return Err("--players does not match the number of --seats".to_owned()),
+        (Some(x), _) | (None, Some(x)) => x,
+        (None, None) => options.num_players,
+    };
+    if options.num_players == 0 || options.num_players > MAX_PLAYERS {
+        return Err(format!("There must be between 1 and {} players", MAX_PLAYERS));
+    }
+    if options.target_score == 0 {
+        return Err("Target score must be a positive number".to_owned());
+    }
+    if let Some((seat, _)) = options.bot_seats.iter().find(|(x, _)| *x >= options.num_players) {
+        return Err(format!("Seat {} does not exist", seat_label(*seat)));
+    }
+    Ok(options)
+}
+
+/// Reads the value of a numeric option, failing rather than wrapping if it does not fit in `T`
+pub fn parse_number<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("{} must be a number", arg))
+}
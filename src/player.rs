@@ -0,0 +1,160 @@
+extern crate rand;
+
+use cards::{CardType, Color};
+use game_state::{Action, GameState, Pending, WILD_COLORS};
+use player_view::PlayerView;
+use self::rand::{RngExt, SeedableRng};
+use self::rand::rngs::Xoshiro256PlusPlus;
+use self::rand::seq::IndexedRandom;
+
+/// Something a player decides on their turn
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Act(Action),
+    /// Save the match to the given file
+    Save(String),
+    /// Replace the match with the one in the given file
+    Load(String),
+    /// Take back the last action
+    Undo,
+    /// Apply the last undone action again
+    Redo,
+    /// Show something the player's view does not hold
+    Show(Info),
+    /// Stop playing
+    Quit,
+}
+
+/// What a person at the terminal can ask to see about the match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Info {
+    /// What has happened so far this round
+    History,
+    /// Each player's score
+    Score,
+}
+
+/// Decides what one seat at the table does. A Player is only ever shown its own PlayerView
+pub trait Player {
+    /// Chooses an action on the viewer's turn. A wild card may be played without a color
+    /// (`Color::Any`), in which case `choose_color` is asked for one
+    fn choose_action(&mut self, view: &PlayerView) -> Action;
+
+    /// Chooses the color of a wild card the viewer is playing, or of a starting Wild
+    fn choose_color(&mut self, view: &PlayerView) -> Color;
+
+    /// Chooses what to do on the viewer's turn. Only a person at the terminal
+    /// asks for anything other than an action
+    fn choose_command(&mut self, view: &PlayerView) -> Command {
+        Command::Act(self.choose_action(view))
+    }
+
+    /// Called after each action while it is not the viewer's turn.
+    /// May return a jump-in, or a catch of a player who did not call UNO
+    fn react(&mut self, _view: &PlayerView) -> Option<Action> {
+        None
+    }
+}
+
+/// Asks the player for a command, filling in the color of any wild card from `choose_color`
+pub fn choose_command(player: &mut dyn Player, view: &PlayerView) -> Command {
+    if let Some(Pending::ChooseColor) = view.pending {
+        return Command::Act(Action::ChooseColor(player.choose_color(view)));
+    }
+    match player.choose_command(view) {
+        Command::Act(Action::Play(mut card, index)) => {
+            match card.card_type {
+                CardType::Wild(Color::Any) => card.card_type = CardType::Wild(player.choose_color(view)),
+                CardType::WildPlus4(Color::Any) => card.card_type = CardType::WildPlus4(player.choose_color(view)),
+                _ => {},
+            }
+            Command::Act(Action::Play(card, index))
+        },
+        command => command,
+    }
+}
+
+/// Plays a game between the players, with nothing printed, until someone wins or
+/// `max_actions` actions have been taken. Returns the number of actions taken.
+/// A rejected action, or a request other than an action, is replaced by the first legal action.
+/// After each action every other player may react
+pub fn play_game(game_state: &mut GameState, players: &mut [Box<dyn Player>], max_actions: usize) -> usize {
+    let mut actions: usize = 0;
+    while game_state.winner().is_none() && actions < max_actions {
+        let current: usize = game_state.players.current_player;
+        let view: PlayerView = game_state.view_for(current);
+        let accepted: bool = match choose_command(players[current].as_mut(), &view) {
+            Command::Act(action) => game_state.apply(action).is_ok(),
+            _ => false,
+        };
+        if !accepted {
+            game_state.apply(view.legal_actions[0]).expect("Expected legal actions to apply");
+        }
+        actions += 1;
+
+        for (player, other) in players.iter_mut().enumerate() {
+            if player == game_state.players.current_player || game_state.winner().is_some() {
+                continue;
+            }
+            if let Some(action) = other.react(&game_state.view_for(player)) {
+                if game_state.apply(action).is_ok() {
+                    actions += 1;
+                }
+            }
+        }
+    }
+    actions
+}
+
+/// Remembers the turn something was last done in, such as calling UNO, so it is only done once a turn
+#[derive(Clone, Debug, Default)]
+pub struct OncePerTurn {
+    done_at: Option<usize>,
+}
+
+impl OncePerTurn {
+    pub fn new() -> OncePerTurn {
+        OncePerTurn {
+            done_at: None,
+        }
+    }
+
+    /// Returns true the first time this is called during a turn
+    pub fn first_time(&mut self, view: &PlayerView) -> bool {
+        if self.done_at == Some(view.turn) {
+            return false;
+        }
+        self.done_at = Some(view.turn);
+        true
+    }
+}
+
+/// Plays a random legal action, choosing wild colors at random.
+/// Remembers to call UNO half of the time
+pub struct RandomPlayer {
+    rng: Xoshiro256PlusPlus,
+    /// Whether calling UNO has been considered this turn
+    uno_considered: OncePerTurn,
+}
+
+impl RandomPlayer {
+    pub fn new(seed: u64) -> RandomPlayer {
+        RandomPlayer {
+            rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+            uno_considered: OncePerTurn::new(),
+        }
+    }
+}
+
+impl Player for RandomPlayer {
+    fn choose_action(&mut self, view: &PlayerView) -> Action {
+        if view.hand.len() == 2 && view.pending.is_none() && self.uno_considered.first_time(view) && self.rng.random_bool(0.5) {
+            return Action::CallUno;
+        }
+        *view.legal_actions.choose(&mut self.rng).expect("Expected a legal action on the player's turn")
+    }
+
+    fn choose_color(&mut self, _view: &PlayerView) -> Color {
+        *WILD_COLORS.choose(&mut self.rng).unwrap()
+    }
+}
@@ -0,0 +1,282 @@
+use cards::{Card, Color};
+use game_state::{Action, GameState, Pending, TurnOrder, active_color, pending_code, pending_from_code};
+use rules::RuleSet;
+use save::{LoadError, SaveReader, SaveWriter};
+
+/// Everything one player is allowed to know about a GameState.
+/// Opponents' hands and the order of the draw pile are hidden, so a PlayerView
+/// is what bots and remote clients are given instead of the GameState itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlayerView {
+    /// The player this view belongs to
+    pub player: usize,
+    pub hand: Vec<Card>,
+    /// The number of cards in each player's hand, including the viewer's
+    pub hand_sizes: Vec<usize>,
+    /// Every card played so far, with the top card last
+    pub discard_pile: Vec<Card>,
+    pub top_card: Card,
+    /// The color that must be matched to play onto the top card
+    pub active_color: Color,
+    pub turn_order: TurnOrder,
+    pub current_player: usize,
+    /// The number of turns started this round, so that one turn can be told from the next
+    pub turn: usize,
+    pub draw_pile_count: usize,
+    pub pending: Option<Pending>,
+    pub uno_vulnerable: Option<usize>,
+    pub winner: Option<usize>,
+    pub rules: RuleSet,
+    /// The actions the viewer may take right now. On their turn these are
+    /// `GameState::legal_actions`, otherwise any jump-ins they can make
+    pub legal_actions: Vec<Action>,
+}
+
+/// The public information about a GameState, for someone watching a game without playing:
+/// the fields of a PlayerView that every player can see. Every hand is only included if the host allows it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpectatorView {
+    /// The number of cards in each player's hand
+    pub hand_sizes: Vec<usize>,
+    /// Each player's hand, if the spectator may see them
+    pub hands: Option<Vec<Vec<Card>>>,
+    /// Every card played so far, with the top card last
+    pub discard_pile: Vec<Card>,
+    pub top_card: Card,
+    /// The color that must be matched to play onto the top card
+    pub active_color: Color,
+    pub turn_order: TurnOrder,
+    pub current_player: usize,
+    /// The number of turns started this round, so that one turn can be told from the next
+    pub turn: usize,
+    pub draw_pile_count: usize,
+    pub pending: Option<Pending>,
+    pub uno_vulnerable: Option<usize>,
+    pub winner: Option<usize>,
+    pub rules: RuleSet,
+}
+
+impl PlayerView {
+    /// Returns true if it is the viewer's turn
+    pub fn is_my_turn(&self) -> bool {
+        self.player == self.current_player
+    }
+
+    /// Returns the number of players
+    pub fn num_players(&self) -> usize {
+        self.hand_sizes.len()
+    }
+
+    /// Returns true if the viewer could act out of turn, by jumping in or by catching a player who did not call UNO
+    pub fn can_react(&self) -> bool {
+        let can_catch: bool = self.uno_vulnerable.is_some_and(|player| player != self.player && self.hand_sizes[player] == 1);
+        !self.legal_actions.is_empty() || can_catch
+    }
+
+    /// Returns the player after the current one in the turn order
+    pub fn next_player(&self) -> usize {
+        let num_players: usize = self.num_players();
+        match self.turn_order {
+            TurnOrder::Normal => (self.current_player + 1) % num_players,
+            TurnOrder::Reverse => (self.current_player + num_players - 1) % num_players,
+        }
+    }
+
+    /// Returns what someone watching the game could see of this view: everything but the viewer's hand and choices
+    pub fn public_view(&self) -> SpectatorView {
+        SpectatorView {
+            hand_sizes: self.hand_sizes.clone(),
+            hands: None,
+            discard_pile: self.discard_pile.clone(),
+            top_card: self.top_card,
+            active_color: self.active_color,
+            turn_order: self.turn_order,
+            current_player: self.current_player,
+            turn: self.turn,
+            draw_pile_count: self.draw_pile_count,
+            pending: self.pending,
+            uno_vulnerable: self.uno_vulnerable,
+            winner: self.winner,
+            rules: self.rules,
+        }
+    }
+
+    /// Writes this view as `key value` lines. See the `external` module for the format
+    pub fn write_view(&self, writer: &mut SaveWriter) {
+        writer.field("player", self.player);
+        writer.cards("hand", &self.hand);
+        let public: SpectatorView = self.public_view();
+        public.write_hand_sizes(writer);
+        public.write_table(writer);
+        writer.field("legal-actions", self.legal_actions.len());
+        for action in self.legal_actions.iter() {
+            writer.field("legal", action.code());
+        }
+    }
+
+    /// Reads a view written by `write_view`
+    pub fn read_view(reader: &mut SaveReader) -> Result<PlayerView, LoadError> {
+        let player: usize = reader.parse("player")?;
+        let hand: Vec<Card> = reader.cards("hand")?;
+        let hand_sizes: Vec<usize> = reader.list("hand-sizes")?;
+        let public: SpectatorView = SpectatorView::read_table(reader, hand_sizes, None)?;
+        let num_actions: usize = reader.parse("legal-actions")?;
+        let mut legal_actions: Vec<Action> = Vec::new();
+        for _ in 0..num_actions {
+            let (line, code) = reader.field("legal")?;
+            legal_actions.push(Action::from_code(code).map_err(|err| reader.invalid(line, err))?);
+        }
+
+        if player >= public.hand_sizes.len() || public.hand_sizes[player] != hand.len() {
+            return Err(reader.invalid(reader.last_line(), "the view refers to a missing player or card"));
+        }
+        Ok(PlayerView {
+            player: player,
+            hand: hand,
+            hand_sizes: public.hand_sizes,
+            discard_pile: public.discard_pile,
+            top_card: public.top_card,
+            active_color: public.active_color,
+            turn_order: public.turn_order,
+            current_player: public.current_player,
+            turn: public.turn,
+            draw_pile_count: public.draw_pile_count,
+            pending: public.pending,
+            uno_vulnerable: public.uno_vulnerable,
+            winner: public.winner,
+            rules: public.rules,
+            legal_actions: legal_actions,
+        })
+    }
+}
+
+impl SpectatorView {
+    /// Writes this view as `key value` lines. See the `server` module for the format
+    pub fn write_view(&self, writer: &mut SaveWriter) {
+        self.write_hand_sizes(writer);
+        writer.field("show-hands", self.hands.is_some());
+        if let Some(ref hands) = self.hands {
+            for hand in hands.iter() {
+                writer.cards("hand", hand);
+            }
+        }
+        self.write_table(writer);
+    }
+
+    /// Reads a view written by `write_view`
+    pub fn read_view(reader: &mut SaveReader) -> Result<SpectatorView, LoadError> {
+        let hand_sizes: Vec<usize> = reader.list("hand-sizes")?;
+        let hands: Option<Vec<Vec<Card>>> = if reader.parse::<bool>("show-hands")? {
+            Some(hand_sizes.iter().map(|_| reader.cards("hand")).collect::<Result<Vec<Vec<Card>>, LoadError>>()?)
+        } else {
+            None
+        };
+        SpectatorView::read_table(reader, hand_sizes, hands)
+    }
+
+    fn write_hand_sizes(&self, writer: &mut SaveWriter) {
+        let hand_sizes: Vec<String> = self.hand_sizes.iter().map(usize::to_string).collect();
+        writer.field("hand-sizes", hand_sizes.join(" "));
+    }
+
+    /// Writes the fields from the discard pile to the rules, which players and spectators are both sent
+    fn write_table(&self, writer: &mut SaveWriter) {
+        writer.cards("discard-pile", &self.discard_pile);
+        writer.field("active-color", self.active_color.code());
+        writer.turn_order("turn-order", self.turn_order);
+        writer.field("current-player", self.current_player);
+        writer.field("turn", self.turn);
+        writer.field("draw-pile", self.draw_pile_count);
+        writer.field("pending", pending_code(self.pending));
+        writer.optional("uno-vulnerable", self.uno_vulnerable);
+        writer.optional("winner", self.winner);
+        writer.rules("rules", &self.rules);
+    }
+
+    /// Reads the fields written by `write_table`, checking that the players they refer to exist
+    fn read_table(reader: &mut SaveReader, hand_sizes: Vec<usize>, hands: Option<Vec<Vec<Card>>>) -> Result<SpectatorView, LoadError> {
+        let discard_pile: Vec<Card> = reader.cards("discard-pile")?;
+        let top_card: Card = match discard_pile.last() {
+            Some(x) => *x,
+            None => return Err(reader.invalid(reader.last_line(), "there must be a top card")),
+        };
+        let (color_line, active_color) = reader.field("active-color")?;
+        // A starting Wild has no color until the first player chooses one
+        let active_color: Color = match Color::from_code(active_color) {
+            Some(x) => x,
+            None => return Err(reader.invalid(color_line, "not a color")),
+        };
+        let turn_order: TurnOrder = reader.turn_order("turn-order")?;
+        let current_player: usize = reader.parse("current-player")?;
+        let turn: usize = reader.parse("turn")?;
+        let draw_pile_count: usize = reader.parse("draw-pile")?;
+        let (pending_line, pending) = reader.field("pending")?;
+        let pending: Option<Pending> = match pending_from_code(pending) {
+            Some(x) => x,
+            None => return Err(reader.invalid(pending_line, "unknown pending decision")),
+        };
+        let uno_vulnerable: Option<usize> = reader.optional("uno-vulnerable")?;
+        let winner: Option<usize> = reader.optional("winner")?;
+        let rules: RuleSet = reader.rules("rules")?;
+
+        let num_players: usize = hand_sizes.len();
+        let players_valid: bool = [Some(current_player), uno_vulnerable, winner].iter()
+            .all(|x| x.is_none_or(|x| x < num_players));
+        if !players_valid {
+            return Err(reader.invalid(reader.last_line(), "the view refers to a missing player"));
+        }
+        Ok(SpectatorView {
+            hand_sizes: hand_sizes,
+            hands: hands,
+            discard_pile: discard_pile,
+            top_card: top_card,
+            active_color: active_color,
+            turn_order: turn_order,
+            current_player: current_player,
+            turn: turn,
+            draw_pile_count: draw_pile_count,
+            pending: pending,
+            uno_vulnerable: uno_vulnerable,
+            winner: winner,
+            rules: rules,
+        })
+    }
+}
+
+impl GameState {
+    /// Builds the view of this game for a spectator, including every hand if `show_hands` is true
+    pub fn spectator_view(&self, show_hands: bool) -> SpectatorView {
+        let mut view: SpectatorView = self.view_for(self.players.current_player).public_view();
+        if show_hands {
+            view.hands = Some((0..self.players.len()).map(|i| self.players.get_player(i).clone()).collect());
+        }
+        view
+    }
+
+    /// Builds the view of this game that `player` is allowed to see
+    pub fn view_for(&self, player: usize) -> PlayerView {
+        let top_card: Card = *self.top_card();
+        let current_player: usize = self.players.current_player;
+        PlayerView {
+            player: player,
+            hand: self.players.get_player(player).clone(),
+            hand_sizes: (0..self.players.len()).map(|i| self.players.get_player(i).len()).collect(),
+            discard_pile: self.discard_pile().clone(),
+            top_card: top_card,
+            active_color: active_color(&top_card),
+            turn_order: self.turn_order,
+            current_player: current_player,
+            turn: self.turn(),
+            draw_pile_count: self.draw_pile_len(),
+            pending: self.pending(),
+            uno_vulnerable: self.uno_vulnerable(),
+            winner: self.winner(),
+            rules: *self.rules(),
+            legal_actions: if player == current_player {
+                self.legal_actions()
+            } else {
+                self.jump_in_actions(player)
+            },
+        }
+    }
+}
@@ -1,14 +1,14 @@
-use cards::Color;
-use game_state::{Action, Players};
+use cards::{Card, CardType, Color};
+use colorize;
+use game_state::{Action, Pending, parse_seat};
+use player::{Command, Info, Player};
+use player_view::PlayerView;
 use std::io;
 
 
 pub fn read_color_from_stdin() -> Color {
 	loop {
-		println!("What color (R/G/Y/B)?");
-		let mut input = String::new();
-		io::stdin().read_line(&mut input).unwrap();
-		input.pop(); // Remove trailing newline
+		let input: String = read_string_from_stdin(Some("What color (R/G/Y/B)?".to_owned()));
 		use cards::Color::*;
 		match input.as_ref() {
 			"R" | "r" => return Red,
@@ -20,36 +20,275 @@ pub fn read_color_from_stdin() -> Color {
 	}
 }
 
-pub fn read_action_from_stdin<'a>(players:&'a mut Players) -> Action {
+pub fn read_player_from_stdin(num_players: usize) -> usize {
+	loop {
+		let input = read_string_from_stdin(Some("Swap hands with which player?".to_owned()));
+		match parse_seat(&input) {
+			Some(player) if player < num_players => return player,
+			_ => println!("Player does not exist!"),
+		}
+	}
+}
+
+pub fn read_challenge_from_stdin() -> bool {
+	loop {
+		let input = read_string_from_stdin(Some("Challenge it (Y/N)?".to_owned()));
+		match input.as_ref() {
+			"Y" | "y" => return true,
+			"N" | "n" => return false,
+			_ => {},
+		}
+	}
+}
+
+/// A person at the terminal, who types in their choices
+pub struct StdinPlayer;
+
+impl Player for StdinPlayer {
+	fn choose_action(&mut self, view: &PlayerView) -> Action {
+		loop {
+			match read_command_from_stdin(view) {
+				Command::Act(action) => return action,
+				_ => println!("Only actions can be taken right now"),
+			}
+		}
+	}
+
+	fn choose_color(&mut self, _view: &PlayerView) -> Color {
+		read_color_from_stdin()
+	}
+
+	fn choose_command(&mut self, view: &PlayerView) -> Command {
+		read_command_from_stdin(view)
+	}
+}
+
+/// Every command that can be typed on the player's turn, as listed by `help`
+const HELP: [(&str, &str); 17] = [
+	("3", "play the card numbered 3 in your hand"),
+	("r5, blue skip, wild g", "play a card by name. A wild card may be given its color"),
+	("pass, p, draw, d", "draw a card, or keep the card you just drew"),
+	("uno", "call UNO"),
+	("catch 2", "catch player 2 for not calling UNO"),
+	("hand", "show your hand again"),
+	("sort", "sort your hand by color and number, or stop sorting it"),
+	("top", "show the top card and the color to play"),
+	("history", "show what has happened this round"),
+	("score", "show the match scores"),
+	("rules", "show which house rules are on"),
+	("undo", "take back the last action"),
+	("redo", "apply the last undone action again"),
+	("save FILE", "save the match"),
+	("load FILE", "resume a saved match"),
+	("quit, q", "stop playing"),
+	("help, ?", "show this list"),
+];
+
+/// The words that can start a command, for suggestions
+const COMMAND_WORDS: [&str; 17] = [
+	"help", "hand", "sort", "top", "history", "score", "rules", "uno", "catch",
+	"pass", "draw", "undo", "redo", "save", "load", "quit", "exit",
+];
+
+/// The words that can be part of a card's name, for suggestions
+const CARD_WORDS: [&str; 10] = ["red", "green", "blue", "yellow", "skip", "reverse", "draw", "two", "wild", "four"];
+
+/// What a line typed on the player's turn asks for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+	Command(Command),
+	/// Commands that only print something the view holds
+	Help,
+	Hand,
+	Sort,
+	Top,
+	Rules,
+	/// Input that could not be used, and what to tell the player
+	Invalid(String),
+}
+
+/// Reads a command for the viewer's turn. Wild cards are returned without a color unless one was named
+pub fn read_command_from_stdin(view: &PlayerView) -> Command {
+	match view.pending {
+		Some(Pending::SwapHands) => return Command::Act(Action::SwapHands(read_player_from_stdin(view.num_players()))),
+		Some(Pending::Challenge(_)) => return Command::Act(Action::Challenge(read_challenge_from_stdin())),
+		Some(Pending::ChooseColor) => return Command::Act(Action::ChooseColor(read_color_from_stdin())),
+		_ => {},
+	}
 	loop {
 		let input = read_string_from_stdin(Some("Pick a card...".to_owned()));
-		if let Ok(n) = input.parse::<usize>() {
-			if n == 0 {
-				println!("Card does not exist!");
-				continue;
+		match parse_input(&input, view) {
+			Input::Command(command) => return command,
+			Input::Help => {
+				for (example, meaning) in HELP.iter() {
+					println!("{:<24}{}", example, meaning);
+				}
+			},
+			Input::Hand => colorize::print_hand(view),
+			Input::Sort => {
+				let sorted: bool = colorize::toggle_sorted();
+				println!("Your hand is {}", if sorted { "now sorted" } else { "no longer sorted" });
+				colorize::print_hand(view);
+			},
+			Input::Top => println!("Top card is {}. The color to play is {}", colorize::paint_card(&view.top_card), view.active_color),
+			Input::Rules => println!("{}", view.rules),
+			Input::Invalid(message) => println!("{}", message),
+		}
+	}
+}
+
+/// Works out what a line typed on the viewer's turn asks for
+pub fn parse_input(input: &str, view: &PlayerView) -> Input {
+	let input: &str = input.trim();
+	let lowercase: String = input.to_lowercase();
+	// Lowercasing can change a string's length, so the first word is split from the input before it is lowercased
+	let (word, rest): (String, &str) = match input.find(' ') {
+		Some(i) => (input[..i].to_lowercase(), input[i + 1..].trim()),
+		None => (lowercase.clone(), ""),
+	};
+	if let Ok(n) = lowercase.parse::<usize>() {
+		// Cards are numbered from 1, as the hand is listed
+		return match view.hand.get(n.wrapping_sub(1)) {
+			Some(card) => Input::Command(Command::Act(Action::Play(*card, n - 1))),
+			None => Input::Invalid("Card does not exist!".to_owned()),
+		};
+	}
+	let action: Action = match (word.as_ref(), rest.is_empty()) {
+		("help", true) | ("?", true) => return Input::Help,
+		("hand", true) => return Input::Hand,
+		("sort", true) => return Input::Sort,
+		("top", true) => return Input::Top,
+		("rules", true) => return Input::Rules,
+		("history", true) => return Input::Command(Command::Show(Info::History)),
+		("score", true) | ("scores", true) => return Input::Command(Command::Show(Info::Score)),
+		("undo", true) => return Input::Command(Command::Undo),
+		("redo", true) => return Input::Command(Command::Redo),
+		("quit", true) | ("q", true) | ("exit", true) => return Input::Command(Command::Quit),
+		// File names keep their case
+		("save", false) => return Input::Command(Command::Save(rest.to_owned())),
+		("load", false) => return Input::Command(Command::Load(rest.to_owned())),
+		("uno", true) => Action::CallUno,
+		("catch", false) => match parse_seat(rest) {
+			Some(player) if player < view.num_players() => Action::Catch(view.player, player),
+			_ => return Input::Invalid("Player does not exist!".to_owned()),
+		},
+		// Passing after drawing a playable card keeps it, otherwise it draws
+		("pass", true) | ("p", true) | ("draw", true) | ("d", true) => match view.pending {
+			Some(Pending::DrawnCard(_)) => Action::Pass,
+			_ => Action::Draw,
+		},
+		_ => match parse_card_name(&lowercase) {
+			Some(named) => match view.hand.iter().position(|card| same_card(card, &named)) {
+				Some(index) => Action::Play(named, index),
+				None => return Input::Invalid(format!("You do not have a {}", named)),
+			},
+			None => return Input::Invalid(match suggest(&lowercase) {
+				Some(suggestion) => format!("{:?} is not valid! Did you mean {:?}? Type \"help\" to see every command", input, suggestion),
+				None => format!("{:?} is not valid! Type a card's number or name, such as 3, r5, blue skip or wild g, or \"help\" to see every command", input),
+			}),
+		},
+	};
+	Input::Command(Command::Act(action))
+}
+
+/// Reads a card's name, such as `r5`, `blue skip`, `green draw two`, `wild g` or `+4 blue`.
+/// A wild card is given the named color as its chosen color, or Any if none is named.
+/// `name` must be lowercase
+pub fn parse_card_name(name: &str) -> Option<Card> {
+	let words: Vec<&str> = name.split_whitespace().collect();
+	let (color, card_type): (Color, CardType) = match words.len() {
+		0 => return None,
+		// Either a wild card, or a color's name or letter joined to the card's type, like "r5" or "blueskip"
+		1 => match card_type_name(words[0]) {
+			Some(x) => (Color::Any, x),
+			None => ["red", "green", "blue", "yellow", "r", "g", "b", "y"].iter()
+				.filter_map(|prefix| words[0].strip_prefix(prefix).map(|rest| (color_name(prefix), card_type_name(rest))))
+				.find_map(|(color, card_type)| Some((color?, card_type?)))?,
+		},
+		// The color comes first, or last for a wild card
+		n => match (color_name(words[0]), color_name(words[n - 1])) {
+			(Some(color), _) => (color, card_type_name(&words[1..].concat())?),
+			(None, Some(color)) => (color, card_type_name(&words[..n - 1].concat())?),
+			(None, None) => (Color::Any, card_type_name(&words.concat())?),
+		},
+	};
+	match card_type {
+		CardType::Wild(_) => Card::new_from(Color::Any, CardType::Wild(color)).ok(),
+		CardType::WildPlus4(_) => Card::new_from(Color::Any, CardType::WildPlus4(color)).ok(),
+		_ => Card::new_from(color, card_type).ok(),
+	}
+}
+
+fn color_name(name: &str) -> Option<Color> {
+	match name {
+		"r" | "red" => Some(Color::Red),
+		"g" | "green" => Some(Color::Green),
+		"b" | "blue" => Some(Color::Blue),
+		"y" | "yellow" => Some(Color::Yellow),
+		_ => None,
+	}
+}
+
+/// Reads the type part of a card's name, with any spaces removed. Wild cards have no chosen color
+fn card_type_name(name: &str) -> Option<CardType> {
+	match name {
+		"skip" | "s" => Some(CardType::Skip),
+		"reverse" | "rev" => Some(CardType::Reverse),
+		"+2" | "plus2" | "draw2" | "drawtwo" => Some(CardType::Plus2),
+		"wild" | "w" => Some(CardType::Wild(Color::Any)),
+		"wild+4" | "+4" | "wildplus4" | "plus4" | "wilddraw4" | "wilddrawfour" | "draw4" | "drawfour" => Some(CardType::WildPlus4(Color::Any)),
+		_ => match name.parse::<i32>() {
+			Ok(x) if name.len() == 1 => Some(CardType::Number(x)),
+			_ => None,
+		},
+	}
+}
+
+/// Whether a card in hand is the named card. Wild cards match whatever color was named
+fn same_card(card: &Card, named: &Card) -> bool {
+	match (card.card_type, named.card_type) {
+		(CardType::Wild(_), CardType::Wild(_)) | (CardType::WildPlus4(_), CardType::WildPlus4(_)) => true,
+		_ => card == named,
+	}
+}
+
+/// Returns the input with each misspelt word corrected, if that makes a command or a card's name
+pub fn suggest(input: &str) -> Option<String> {
+	let words: Vec<String> = input.split_whitespace()
+		.map(|word| {
+			let known = COMMAND_WORDS.iter().chain(CARD_WORDS.iter());
+			// Short words have fewer letters to get wrong
+			let allowed: usize = if word.chars().count() <= 4 { 1 } else { 2 };
+			match known.map(|x| (edit_distance(word, x), x)).min() {
+				Some((distance, x)) if distance <= allowed => x.to_string(),
+				_ => word.to_owned(),
 			}
+		})
+		.collect();
+	let corrected: String = words.join(" ");
+	let is_command: bool = words.len() == 1 && COMMAND_WORDS.contains(&words[0].as_ref());
+	if corrected != input && (is_command || parse_card_name(&corrected).is_some()) {
+		Some(corrected)
+	} else {
+		None
+	}
+}
 
-			// Minus 1 because humans are 1-indexed
-			let card_index: usize = n - 1 as usize;
-			match players.get_from_current_player_mut(card_index) {
-				None => println!("Card does not exist!"),
-				Some(x) => {
-					// If wild, ask for color
-					use cards::CardType::*;
-					match x.card_type {
-						Wild(_) => x.card_type = Wild(read_color_from_stdin()),
-						WildPlus4(_) => x.card_type = WildPlus4(read_color_from_stdin()),
-						_ => {},
-					}
-					return Action::Play(*x, card_index);
-				},
-			}	
-		} else if input == "pass" || input == "p" {
-			return Action::Draw;
-		} else {
-			println!("{:?} is not valid!", input);
+/// The number of letters that must be added, removed, changed or swapped with the next to turn one word into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+	let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+	// distances[i][j] is the distance between the first i letters of a and the first j letters of b
+	let mut distances: Vec<Vec<usize>> = (0..=a.len()).map(|i| (0..=b.len()).map(|j| if i == 0 { j } else if j == 0 { i } else { 0 }).collect()).collect();
+	for i in 1..=a.len() {
+		for j in 1..=b.len() {
+			let change: usize = distances[i - 1][j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+			distances[i][j] = change.min(distances[i - 1][j] + 1).min(distances[i][j - 1] + 1);
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+			}
 		}
 	}
+	distances[a.len()][b.len()]
 }
 
 pub fn read_string_from_stdin(message: Option<String>) -> String {
@@ -57,7 +296,11 @@ pub fn read_string_from_stdin(message: Option<String>) -> String {
 		println!("{}", x);
 	}
 	let mut input = String::new();
-	io::stdin().read_line(&mut input).unwrap();
+	// Piped input that runs out would otherwise be asked again forever
+	if io::stdin().read_line(&mut input).unwrap() == 0 {
+		println!("No more input");
+		std::process::exit(0);
+	}
 	input.pop(); // Remove trailing newline
 	input
 }
\ No newline at end of file
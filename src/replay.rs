@@ -0,0 +1,141 @@
+//! A replay holds everything needed to play a round again from the deal:
+//! the seed, the rules, the players and every action that was applied.
+//!
+//! ```text
+//! uno-replay 1
+//! players 4
+//! seed 1235
+//! first-player 1
+//! rules stacking
+//! actions 3
+//! action play 2 r5               one line per action, written with `Action::code`
+//! action draw
+//! action pass
+//! ```
+
+use game_state::{Action, GameState, MAX_PLAYERS, RuleError};
+use rules::RuleSet;
+use save::{LoadError, SaveReader, SaveWriter, read_save_file, write_save_file};
+
+pub const REPLAY_HEADER: &str = "uno-replay";
+pub const REPLAY_VERSION: u32 = 1;
+
+/// The actions of one round, along with how it was dealt
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    num_players: usize,
+    seed: u64,
+    first_player: usize,
+    rules: RuleSet,
+    actions: Vec<Action>,
+}
+
+impl Replay {
+    /// Starts a replay of a round dealt by `GameState::deal` with the same arguments
+    pub fn new(num_players: usize, seed: u64, rules: RuleSet, first_player: usize) -> Replay {
+        Replay {
+            num_players: num_players,
+            seed: seed,
+            first_player: first_player,
+            rules: rules,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Adds an action that was successfully applied to the round
+    pub fn record(&mut self, action: Action) {
+        self.actions.push(action);
+    }
+
+    /// Removes the last action, after it was undone
+    pub fn undo(&mut self) {
+        self.actions.pop();
+    }
+
+    pub fn actions(&self) -> &Vec<Action> {
+        &self.actions
+    }
+
+    /// Deals the round again, before any actions
+    pub fn start(&self) -> GameState {
+        GameState::deal(self.num_players, self.seed, self.rules, self.first_player)
+            .expect("Expected a replay to be of a round that was dealt")
+    }
+
+    /// Returns the state of the round after each number of actions, starting from the deal
+    pub fn states(&self) -> Vec<GameState> {
+        self.play().expect("Replays only hold actions that were applied")
+    }
+
+    /// Plays the round again, returning every state or the index of the first action that fails
+    fn play(&self) -> Result<Vec<GameState>, (usize, RuleError)> {
+        let mut states: Vec<GameState> = vec!(self.start());
+        for (i, action) in self.actions.iter().enumerate() {
+            let mut game_state: GameState = states[i].clone();
+            game_state.apply(*action).map_err(|err| (i, err))?;
+            states.push(game_state);
+        }
+        Ok(states)
+    }
+
+    /// Returns this replay as the text of a replay file
+    pub fn save(&self) -> String {
+        let mut writer: SaveWriter = SaveWriter::with_header(REPLAY_HEADER, REPLAY_VERSION);
+        writer.field("players", self.num_players);
+        writer.field("seed", self.seed);
+        writer.field("first-player", self.first_player);
+        writer.rules("rules", &self.rules);
+        writer.field("actions", self.actions.len());
+        for action in self.actions.iter() {
+            writer.field("action", action.code());
+        }
+        writer.finish()
+    }
+
+    /// Reads a replay from the text of a replay file, checking that every action can be applied
+    pub fn load(text: &str) -> Result<Replay, LoadError> {
+        let mut reader: SaveReader = SaveReader::with_header(text, REPLAY_HEADER, REPLAY_VERSION)?;
+        let num_players: usize = reader.parse("players")?;
+        // The round is dealt again from the seed, so the deck must have enough cards for everyone
+        if num_players == 0 || num_players > MAX_PLAYERS {
+            return Err(reader.invalid(reader.last_line(), &format!("there must be between 1 and {} players", MAX_PLAYERS)));
+        }
+        let seed: u64 = reader.parse("seed")?;
+        let first_player: usize = reader.parse("first-player")?;
+        if first_player >= num_players {
+            return Err(reader.invalid(reader.last_line(), "no such player"));
+        }
+        let rules: RuleSet = reader.rules("rules")?;
+        let num_actions: usize = reader.parse("actions")?;
+        let mut actions: Vec<Action> = Vec::new();
+        let mut lines: Vec<usize> = Vec::new();
+        for _ in 0..num_actions {
+            let (line, code) = reader.field("action")?;
+            actions.push(Action::from_code(code).map_err(|err| reader.invalid(line, err))?);
+            lines.push(line);
+        }
+        reader.finish()?;
+
+        let replay: Replay = Replay {
+            num_players: num_players,
+            seed: seed,
+            first_player: first_player,
+            rules: rules,
+            actions: actions,
+        };
+        if let Err((i, err)) = replay.play() {
+            return Err(LoadError::Invalid(lines[i], format!("action could not be applied: {}", err)));
+        }
+        Ok(replay)
+    }
+
+    /// Saves this replay to a file
+    pub fn save_to_file(&self, path: &str) -> Result<(), LoadError> {
+        write_save_file(path, &self.save())
+    }
+
+    /// Loads a replay from a file
+    pub fn load_from_file(path: &str) -> Result<Replay, LoadError> {
+        Replay::load(&read_save_file(path)?)
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+
+/// Toggles for common house rules. The default RuleSet plays by the official rules
+/// (every toggle off)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    /// A player facing a Plus2 or WildPlus4 may stack another onto it, passing the
+    /// combined penalty on to the next player. A Plus2 may be stacked onto a Plus2,
+    /// and a WildPlus4 onto either. A WildPlus4 cannot be challenged when stacking
+    pub stacking: bool,
+    /// Drawing keeps drawing until a playable card is drawn
+    pub draw_until_playable: bool,
+    /// A playable card that was just drawn may be played right away
+    pub play_drawn_card: bool,
+    /// A player who can play a card may not draw
+    pub forced_play: bool,
+    /// Playing a 7 swaps hands with a chosen player, and playing a 0 passes every
+    /// hand along in the direction of play
+    pub seven_zero: bool,
+    /// A player holding a card identical to the top card may play it out of turn
+    pub jump_in: bool,
+    /// The starting card has no effect, and wild cards are never used as the starting card
+    pub plain_starting_card: bool,
+}
+
+/// The name of each rule, as used by `RuleSet::toggle`
+pub const RULE_NAMES: [&str; 7] = [
+    "stacking",
+    "draw-until-playable",
+    "play-drawn-card",
+    "forced-play",
+    "seven-zero",
+    "jump-in",
+    "plain-starting-card",
+];
+
+/// The name of each preset, as used by `RuleSet::preset`
+pub const PRESET_NAMES: [&str; 3] = ["official", "house", "party"];
+
+impl RuleSet {
+    /// The official rules, with no house rules enabled
+    pub fn official() -> RuleSet {
+        RuleSet::default()
+    }
+
+    /// Returns a named set of rules, or None if there is no such preset.
+    /// "house" is the way many families play, stacking draw cards and drawing until they can play.
+    /// "party" adds 7-0 and jumping in on top
+    pub fn preset(name: &str) -> Option<RuleSet> {
+        let house: RuleSet = RuleSet {
+            stacking: true,
+            draw_until_playable: true,
+            play_drawn_card: true,
+            ..RuleSet::official()
+        };
+        match name {
+            "official" => Some(RuleSet::official()),
+            "house" => Some(house),
+            "party" => Some(RuleSet {
+                seven_zero: true,
+                jump_in: true,
+                ..house
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the toggle with the given name
+    fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "stacking" => Some(&mut self.stacking),
+            "draw-until-playable" => Some(&mut self.draw_until_playable),
+            "play-drawn-card" => Some(&mut self.play_drawn_card),
+            "forced-play" => Some(&mut self.forced_play),
+            "seven-zero" => Some(&mut self.seven_zero),
+            "jump-in" => Some(&mut self.jump_in),
+            "plain-starting-card" => Some(&mut self.plain_starting_card),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the rule with the given name, or None if there is no such rule
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let mut rules: RuleSet = *self;
+        rules.get_mut(name).map(|x| *x)
+    }
+
+    /// Turns the rule with the given name on or off.
+    /// Returns the new value, or None if there is no such rule
+    pub fn toggle(&mut self, name: &str) -> Option<bool> {
+        match self.get_mut(name) {
+            Some(x) => {
+                *x = !*x;
+                Some(*x)
+            },
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, name) in RULE_NAMES.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            let on: bool = self.get(name).unwrap();
+            write!(f, "{}: {}", name, if on { "on" } else { "off" })?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+//! Save files are plain text, one `key value` field per line, in a fixed order.
+//! The first line is always `uno-save <version>`, and files from another version are rejected.
+//!
+//! Version 1 saves a match, then the round in progress:
+//!
+//! ```text
+//! uno-save 1
+//! target 500                     score needed to win the match
+//! dealer 0
+//! round 0                        rounds dealt before this one
+//! match-seed 1234
+//! scores 0 0 0 0                 one score per player
+//! seed 1235                      the round's seed
+//! shuffles 1                     how many times the round's draw deck has been shuffled
+//! rules stacking jump-in         the house rules that are on
+//! turn-order normal              normal or reverse
+//! current-player 0
+//! turn 0                         the number of turns started this round
+//! winner none                    none or a player
+//! pending none                   none, drawn-card N, draw-penalty N, swap-hands, choose-color or challenge N
+//! uno-called false
+//! uno-vulnerable none            none or a player
+//! bluffed false                  whether the last WildPlus4 could be challenged successfully
+//! draw-deck r5 gskip wild ...    bottom card first
+//! play-deck y+2 wild+4:b ...     bottom card first, so the top card is last
+//! hands 4
+//! hand r1 r2 b7                  one line per player
+//! ```
+//!
+//! Cards are written with `Card::code`. Players are 0-indexed.
+//! The event log is not saved.
+//!
+//! Replay files use the same `key value` lines under their own header. See the `replay` module.
+
+use cards::Card;
+use game_state::TurnOrder;
+use rules::{RULE_NAMES, RuleSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+pub const SAVE_HEADER: &str = "uno-save";
+pub const SAVE_VERSION: u32 = 1;
+
+/// The reasons a save file may fail to load
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// The file could not be read or written
+    Io(String),
+    /// The file does not start with the save header
+    NotASave,
+    /// The file was saved by a different version
+    WrongVersion(u32),
+    /// The file ended before this field
+    Missing(&'static str),
+    /// The line with this (1-indexed) number is not valid
+    Invalid(usize, String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::LoadError::*;
+        match *self {
+            Io(ref message) => write!(f, "Could not access save file: {}", message),
+            NotASave => write!(f, "Not an uno save or replay file"),
+            WrongVersion(version) => write!(f, "File is version {}, which this version of uno cannot read", version),
+            Missing(key) => write!(f, "File ended before \"{}\"", key),
+            Invalid(line, ref message) => write!(f, "Line {} is not valid: {}", line, message),
+        }
+    }
+}
+
+/// Writes `key value` lines for a save file
+pub struct SaveWriter {
+    text: String,
+}
+
+impl SaveWriter {
+    /// Starts a save file with the header line
+    pub fn new() -> SaveWriter {
+        SaveWriter::with_header(SAVE_HEADER, SAVE_VERSION)
+    }
+
+    /// Starts a file with a different header line
+    pub fn with_header(header: &str, version: u32) -> SaveWriter {
+        SaveWriter {
+            text: format!("{} {}\n", header, version),
+        }
+    }
+
+    pub fn field<T: fmt::Display>(&mut self, key: &str, value: T) {
+        self.text.push_str(&format!("{} {}\n", key, value));
+    }
+
+    /// Writes `none` or the value
+    pub fn optional<T: fmt::Display>(&mut self, key: &str, value: Option<T>) {
+        match value {
+            Some(x) => self.field(key, x),
+            None => self.field(key, "none"),
+        }
+    }
+
+    pub fn cards(&mut self, key: &str, cards: &[Card]) {
+        let codes: Vec<String> = cards.iter().map(Card::code).collect();
+        self.field(key, codes.join(" "));
+    }
+
+    /// Writes the names of the rules that are on
+    pub fn rules(&mut self, key: &str, rules: &RuleSet) {
+        let names: Vec<&str> = RULE_NAMES.iter().cloned().filter(|name| rules.get(name) == Some(true)).collect();
+        self.field(key, names.join(" "));
+    }
+
+    pub fn turn_order(&mut self, key: &str, turn_order: TurnOrder) {
+        self.field(key, match turn_order {
+            TurnOrder::Normal => "normal",
+            TurnOrder::Reverse => "reverse",
+        });
+    }
+
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+/// Reads the `key value` lines of a save file in order
+pub struct SaveReader<'a> {
+    lines: Vec<(usize, &'a str)>,
+    next: usize,
+}
+
+impl<'a> SaveReader<'a> {
+    /// Checks the save header line and version, then reads the remaining lines
+    pub fn new(text: &'a str) -> Result<SaveReader<'a>, LoadError> {
+        SaveReader::with_header(text, SAVE_HEADER, SAVE_VERSION)
+    }
+
+    /// Checks for a different header line and version, then reads the remaining lines
+    pub fn with_header(text: &'a str, header: &str, version: u32) -> Result<SaveReader<'a>, LoadError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+        let first_line: &str = match lines.next() {
+            Some((_, line)) => line,
+            None => return Err(LoadError::NotASave),
+        };
+        let mut words = first_line.split_whitespace();
+        if words.next() != Some(header) {
+            return Err(LoadError::NotASave);
+        }
+        match words.next().map(|x| x.parse::<u32>()) {
+            Some(Ok(found)) if found == version => {},
+            Some(Ok(found)) => return Err(LoadError::WrongVersion(found)),
+            _ => return Err(LoadError::NotASave),
+        }
+        Ok(SaveReader {
+            lines: lines.filter(|&(_, line)| !line.is_empty()).collect(),
+            next: 0,
+        })
+    }
+
+    /// Reads the next line, which must start with `key`, and returns its line number and value
+    pub fn field(&mut self, key: &'static str) -> Result<(usize, &'a str), LoadError> {
+        let (number, line) = match self.lines.get(self.next) {
+            Some(&x) => x,
+            None => return Err(LoadError::Missing(key)),
+        };
+        self.next += 1;
+        let (found, value) = match line.find(' ') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => (line, ""),
+        };
+        if found != key {
+            return Err(LoadError::Invalid(number, format!("expected \"{}\", found \"{}\"", key, found)));
+        }
+        Ok((number, value))
+    }
+
+    /// Returns the line number of the last field read
+    pub fn last_line(&self) -> usize {
+        match self.next {
+            0 => 1,
+            n => self.lines[n - 1].0,
+        }
+    }
+
+    pub fn parse<T: FromStr>(&mut self, key: &'static str) -> Result<T, LoadError> {
+        let (number, value) = self.field(key)?;
+        parse_value(number, value)
+    }
+
+    /// Reads a value that may be `none`
+    pub fn optional<T: FromStr>(&mut self, key: &'static str) -> Result<Option<T>, LoadError> {
+        let (number, value) = self.field(key)?;
+        if value == "none" {
+            return Ok(None);
+        }
+        parse_value(number, value).map(Some)
+    }
+
+    /// Reads a line of whitespace separated values
+    pub fn list<T: FromStr>(&mut self, key: &'static str) -> Result<Vec<T>, LoadError> {
+        let (number, value) = self.field(key)?;
+        value.split_whitespace().map(|x| parse_value(number, x)).collect()
+    }
+
+    pub fn cards(&mut self, key: &'static str) -> Result<Vec<Card>, LoadError> {
+        let (number, value) = self.field(key)?;
+        value.split_whitespace()
+            .map(|code| Card::from_code(code).map_err(|err| LoadError::Invalid(number, format!("{}: {}", code, err))))
+            .collect()
+    }
+
+    /// Reads the names of the rules that are on. Every other rule is off
+    pub fn rules(&mut self, key: &'static str) -> Result<RuleSet, LoadError> {
+        let (number, names) = self.field(key)?;
+        let mut rules: RuleSet = RuleSet::official();
+        for name in names.split_whitespace() {
+            if rules.toggle(name) != Some(true) {
+                return Err(LoadError::Invalid(number, format!("unknown or repeated rule \"{}\"", name)));
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn turn_order(&mut self, key: &'static str) -> Result<TurnOrder, LoadError> {
+        match self.field(key)? {
+            (_, "normal") => Ok(TurnOrder::Normal),
+            (_, "reverse") => Ok(TurnOrder::Reverse),
+            (number, _) => Err(LoadError::Invalid(number, "turn order must be normal or reverse".to_owned())),
+        }
+    }
+
+    /// Returns an error for a value on the given line
+    pub fn invalid(&self, number: usize, message: &str) -> LoadError {
+        LoadError::Invalid(number, message.to_owned())
+    }
+
+    /// Checks that every line has been read
+    pub fn finish(self) -> Result<(), LoadError> {
+        match self.lines.get(self.next) {
+            Some(&(number, _)) => Err(LoadError::Invalid(number, "unexpected extra line".to_owned())),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(number: usize, value: &str) -> Result<T, LoadError> {
+    value.parse::<T>().map_err(|_| LoadError::Invalid(number, format!("\"{}\" is not a valid value", value)))
+}
+
+/// Writes a save file to disk
+pub fn write_save_file(path: &str, text: &str) -> Result<(), LoadError> {
+    let mut file: File = File::create(path).map_err(|err| LoadError::Io(err.to_string()))?;
+    file.write_all(text.as_bytes()).map_err(|err| LoadError::Io(err.to_string()))
+}
+
+/// Reads a save file from disk
+pub fn read_save_file(path: &str) -> Result<String, LoadError> {
+    let mut text: String = String::new();
+    let mut file: File = File::open(path).map_err(|err| LoadError::Io(err.to_string()))?;
+    file.read_to_string(&mut text).map_err(|err| LoadError::Io(err.to_string()))?;
+    Ok(text)
+}
@@ -0,0 +1,526 @@
+//! `uno serve` hosts matches for players who join over TCP, so that each player only
+//! sees their own hand. People join with `uno connect` and watch with `uno watch`,
+//! and programs can do either too.
+//!
+//! The server speaks the protocol of external bots (see the `external` module),
+//! with a lobby in front of it and a few more messages:
+//!
+//! ```text
+//! uno-server 1                   server: sent when a client connects, with the protocol version
+//! join Alice                     client: the player's name, or `watch` and a name to spectate
+//! message Bob joined (2/3)       server: text to show the player
+//! seat 2                         server: the match has started and the player is in this seat
+//! uno-view 1 ... end             server: the player's view, as sent to external bots
+//! go                             server: it is the player's turn
+//! play 1 gskip                   client: an action, written with `Action::code`
+//! play 3 wild                    client: a wild card sent without a color is followed by the view and `go` again
+//! color r                        client: the reply to that must be `color` and the color chosen
+//! error Card is not in your hand server: the reply was rejected, and the view and `go` are sent again
+//! react                          server: after the view, when the player could jump in or catch someone out of turn
+//! none                           client: `none`, or a jump-in or catch made by the player's own seat. A rejected reply is not asked for again
+//! quit                           server: the match is over
+//! uno-spectate 1                 server: sent to spectators after each action
+//! hand-sizes 7 3                 one per player
+//! show-hands true                whether the host lets spectators see every hand
+//! hand r1 b7 wild r2 g+2 y0 y3   one line per player, only if hands are shown
+//! hand b1 b2 gskip
+//! discard-pile ... rules ...     the rest is as in a player's view, without the legal actions
+//! end
+//! ```
+//!
+//! Spectators get the same messages as players, and may arrive at any time.
+//! A match starts once the lobby is full. A player who disconnects, or does not reply in time,
+//! is replaced by a Medium bot for the rest of the match. Players who join during a match
+//! wait in the lobby for the next one
+
+use bots::{Difficulty, HeuristicPlayer};
+use cards::Color;
+use external::{Connection, ExternalError};
+use game_match::{DEFAULT_TARGET_SCORE, Match};
+use game_state::{Action, GameState, MAX_PLAYERS, seat_label};
+use options::parse_number;
+use player::{Command, Player, choose_command};
+use player_view::PlayerView;
+use rules::RuleSet;
+use save::SaveWriter;
+use std::io;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::thread;
+use std::time::Duration;
+
+pub const SERVER_HEADER: &str = "uno-server";
+pub const SERVER_VERSION: u32 = 1;
+pub const SPECTATE_HEADER: &str = "uno-spectate";
+pub const DEFAULT_PORT: u16 = 7654;
+/// How long a player has to take their turn when no other timeout is given
+pub const DEFAULT_TURN_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long a client has to send its name after connecting
+const JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub const SERVE_USAGE: &str = "Usage: uno serve [--port N] [--players N] [--target N] [--timeout SECONDS] [--rules rule,...] [--show-hands]";
+
+/// The options for a server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerConfig {
+    /// The port to listen on, or 0 for any free port
+    pub port: u16,
+    /// The number of players in each match
+    pub num_players: usize,
+    pub rules: RuleSet,
+    pub target_score: u32,
+    /// How long a player has to take their turn
+    pub timeout: Duration,
+    /// Lets spectators see every hand
+    pub show_hands: bool,
+}
+
+/// A player connected to the server
+struct RemotePlayer {
+    name: String,
+    /// Kept to close the connection when the player leaves
+    stream: TcpStream,
+    connection: Connection,
+    /// Plays the seat once the player has left
+    fallback: Option<HeuristicPlayer>,
+    /// Whether the other players have been told that this player left
+    departure_announced: bool,
+}
+
+impl RemotePlayer {
+    /// Sends text to show the player, if they are still connected
+    fn message(&mut self, text: &str) {
+        if self.fallback.is_none() {
+            let _ = self.connection.send(&format!("message {}", text));
+        }
+    }
+
+    /// Tells the player why their last reply was rejected
+    fn reject(&mut self, reason: &str) {
+        if self.fallback.is_none() {
+            let _ = self.connection.send(&format!("error {}", reason));
+        }
+    }
+
+    /// Sends the view and asks for an action until one can be read.
+    /// Returns None once the player has left
+    fn request(&mut self, view: &PlayerView) -> Option<Action> {
+        while self.fallback.is_none() {
+            let reply: Result<String, ExternalError> = self.connection.send_view(view, "go")
+                .and_then(|_| self.connection.receive());
+            match reply {
+                Ok(reply) => match Action::from_code(&reply) {
+                    Ok(Action::JumpIn(player, _)) | Ok(Action::Catch(player, _)) if player != view.player => {
+                        self.reject("Actions can only be made by your own seat");
+                    },
+                    Ok(action) => return Some(action),
+                    Err(err) => self.reject(err),
+                },
+                Err(err) => self.leave(&err),
+            }
+        }
+        None
+    }
+
+    /// Closes the connection and hands the seat to a bot
+    fn leave(&mut self, reason: &ExternalError) {
+        let _ = self.connection.send(&format!("message {}. A bot plays your seat now", reason));
+        self.connection.close();
+        let _ = self.stream.shutdown(Shutdown::Both);
+        self.fallback = Some(HeuristicPlayer::new(Difficulty::Medium));
+    }
+
+    /// Sends the view and asks whether the player acts out of turn.
+    /// Returns None if they do not, or have just left
+    fn request_reaction(&mut self, view: &PlayerView) -> Option<Action> {
+        let reply: Result<String, ExternalError> = self.connection.send_view(view, "react")
+            .and_then(|_| self.connection.receive());
+        match reply {
+            Ok(ref reply) if reply == "none" => None,
+            Ok(reply) => match Action::from_code(&reply) {
+                Ok(action @ Action::JumpIn(player, _)) | Ok(action @ Action::Catch(player, _)) if player == view.player => Some(action),
+                Ok(_) => {
+                    self.reject("Only a jump-in or a catch by your own seat can be made out of turn");
+                    None
+                },
+                Err(err) => {
+                    self.reject(err);
+                    None
+                },
+            },
+            Err(err) => {
+                self.leave(&err);
+                None
+            },
+        }
+    }
+
+    fn has_left(&self) -> bool {
+        self.fallback.is_some()
+    }
+}
+
+impl Player for RemotePlayer {
+    fn choose_action(&mut self, view: &PlayerView) -> Action {
+        match self.request(view) {
+            Some(action) => action,
+            None => self.fallback.as_mut().unwrap().choose_action(view),
+        }
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        loop {
+            match self.request(view) {
+                Some(Action::ChooseColor(color)) => return color,
+                Some(_) => self.reject("Choose a color"),
+                None => return self.fallback.as_mut().unwrap().choose_color(view),
+            }
+        }
+    }
+
+    fn react(&mut self, view: &PlayerView) -> Option<Action> {
+        if !view.can_react() {
+            return None;
+        }
+        match self.fallback {
+            Some(ref mut fallback) => fallback.react(view),
+            None => self.request_reaction(view),
+        }
+    }
+}
+
+/// Someone watching matches without playing
+struct Spectator {
+    name: String,
+    connection: Connection,
+}
+
+/// A client that has said whether it will play or watch
+enum Arrival {
+    Player(Box<RemotePlayer>),
+    Spectator(Spectator),
+}
+
+/// Greets a new client and reads its name. Returns None if it neither joins nor watches
+fn greet(stream: TcpStream, timeout: Duration) -> Option<Arrival> {
+    let reader: TcpStream = stream.try_clone().ok()?;
+    let writer: TcpStream = stream.try_clone().ok()?;
+    let mut connection: Connection = Connection::new(reader, writer, JOIN_TIMEOUT);
+    connection.send(&format!("{} {}", SERVER_HEADER, SERVER_VERSION)).ok()?;
+    let reply: String = connection.receive().ok()?;
+    let (kind, name): (&str, &str) = match reply.find(' ') {
+        Some(i) => (&reply[..i], reply[i + 1..].trim()),
+        None => (&reply, ""),
+    };
+    match kind {
+        "join" | "watch" if !name.is_empty() => {},
+        _ => {
+            let _ = connection.send("error Expected \"join\" or \"watch\" and a name");
+            let _ = stream.shutdown(Shutdown::Both);
+            return None;
+        },
+    }
+    connection.set_timeout(timeout);
+    if kind == "watch" {
+        return Some(Arrival::Spectator(Spectator {
+            name: name.to_owned(),
+            connection: connection,
+        }));
+    }
+    Some(Arrival::Player(Box::new(RemotePlayer {
+        name: name.to_owned(),
+        stream: stream,
+        connection: connection,
+        fallback: None,
+        departure_announced: false,
+    })))
+}
+
+/// Listens for players and spectators, and hosts one match at a time
+pub struct Server {
+    config: ServerConfig,
+    address: SocketAddr,
+    /// Clients who have said whether they will play or watch, in the order they did
+    arrivals: Receiver<Arrival>,
+    /// Players waiting for a place in the lobby
+    waiting: Vec<RemotePlayer>,
+    spectators: Vec<Spectator>,
+}
+
+impl Server {
+    /// Starts listening. Clients are greeted on their own threads, so a slow one does not hold up the lobby
+    pub fn bind(config: ServerConfig) -> io::Result<Server> {
+        let listener: TcpListener = TcpListener::bind(("0.0.0.0", config.port))?;
+        let address: SocketAddr = listener.local_addr()?;
+        let (sender, arrivals) = channel::<Arrival>();
+        let timeout: Duration = config.timeout;
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    if let Some(arrival) = greet(stream, timeout) {
+                        let _ = sender.send(arrival);
+                    }
+                });
+            }
+        });
+        Ok(Server {
+            config: config,
+            address: address,
+            arrivals: arrivals,
+            waiting: Vec::new(),
+            spectators: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Waits for the lobby to fill, then plays a match and returns it once someone has won
+    pub fn serve_match(&mut self) -> Match {
+        let num_players: usize = self.config.num_players;
+        let mut players: Vec<RemotePlayer> = Vec::new();
+        while players.len() < num_players {
+            if self.waiting.is_empty() {
+                match self.arrivals.recv_timeout(Duration::from_millis(100)) {
+                    Ok(arrival) => self.admit(arrival, None),
+                    Err(RecvTimeoutError::Timeout) => {},
+                    Err(RecvTimeoutError::Disconnected) => panic!("Expected the server to keep listening"),
+                }
+            }
+            while players.len() < num_players && !self.waiting.is_empty() {
+                let player: RemotePlayer = self.waiting.remove(0);
+                println!("{} joined", player.name);
+                let text: String = format!("{} joined ({}/{})", player.name, players.len() + 1, num_players);
+                players.push(player);
+                self.broadcast(&mut players, &text);
+            }
+            // Players who leave the lobby give up their place
+            let mut left: Vec<String> = Vec::new();
+            players.retain_mut(|player| {
+                let closed: bool = player.connection.is_closed();
+                if closed {
+                    left.push(player.name.clone());
+                }
+                !closed
+            });
+            for name in left {
+                println!("{} left", name);
+                let text: String = format!("{} left ({}/{})", name, players.len(), num_players);
+                self.broadcast(&mut players, &text);
+            }
+        }
+
+        println!("Starting a match between {}", players.iter().map(|player| player.name.as_ref()).collect::<Vec<&str>>().join(", "));
+        for (seat, player) in players.iter_mut().enumerate() {
+            let _ = player.connection.send(&format!("seat {}", seat));
+        }
+        for seat in 0..num_players {
+            let text: String = format!("Player {} is {}", seat_label(seat), players[seat].name);
+            self.broadcast(&mut players, &text);
+        }
+
+        let mut uno_match: Match = Match::new(num_players, self.config.rules, self.config.target_score)
+            .expect("Expected the player count to have been checked with the options");
+        loop {
+            self.broadcast(&mut players, &format!("Uno round {} start!", uno_match.round_number() + 1));
+            self.play_round(&mut uno_match, &mut players);
+            let winner: usize = uno_match.round.winner().unwrap();
+            let points: u32 = uno_match.score_round();
+            self.broadcast(&mut players, &format!("Player {} scores {} points", seat_label(winner), points));
+            let scores: Vec<String> = uno_match.scores().iter().enumerate()
+                .map(|(i, score)| format!("Player {}: {}/{}", seat_label(i), score, uno_match.target_score()))
+                .collect();
+            for line in scores {
+                self.broadcast(&mut players, &line);
+            }
+            if let Some(winner) = uno_match.winner() {
+                println!("{} wins the match", players[winner].name);
+                self.broadcast(&mut players, &format!("Player {} wins the match!", seat_label(winner)));
+                break;
+            }
+            uno_match.next_round();
+        }
+        for player in players.iter_mut() {
+            let _ = player.connection.send("quit");
+            player.connection.close();
+        }
+        uno_match
+    }
+
+    /// Adds a new spectator, or puts a new player in line for the lobby.
+    /// `round` is the round being played, if any
+    fn admit(&mut self, arrival: Arrival, round: Option<&GameState>) {
+        match arrival {
+            Arrival::Player(mut player) => {
+                if round.is_some() {
+                    player.message("A match is being played. You will join the lobby once it ends");
+                }
+                self.waiting.push(*player);
+            },
+            Arrival::Spectator(mut spectator) => {
+                println!("{} is watching", spectator.name);
+                let hands: &str = if self.config.show_hands { "Every hand is shown" } else { "Hands are hidden" };
+                let _ = spectator.connection.send(&format!("message You are watching. {}", hands));
+                if let Some(round) = round {
+                    let _ = spectator.connection.send(&spectate_message(round, self.config.show_hands));
+                }
+                self.spectators.push(spectator);
+            },
+        }
+    }
+
+    /// Sends text to show every player who is still connected, and every spectator
+    fn broadcast(&mut self, players: &mut [RemotePlayer], text: &str) {
+        for player in players.iter_mut() {
+            player.message(text);
+        }
+        let line: String = format!("message {}", text);
+        self.spectators.retain_mut(|spectator| spectator.connection.send(&line).is_ok());
+    }
+
+    /// Sends every spectator their view of the round
+    fn show_spectators(&mut self, round: &GameState) {
+        let text: String = spectate_message(round, self.config.show_hands);
+        self.spectators.retain_mut(|spectator| spectator.connection.send(&text).is_ok());
+    }
+
+    /// Lets every player whose turn it is not jump in or catch someone, in seat order
+    fn react(&mut self, uno_match: &mut Match, players: &mut [RemotePlayer]) {
+        for seat in 0..players.len() {
+            if seat == uno_match.round.players.current_player || uno_match.round.winner().is_some() {
+                continue;
+            }
+            let reaction: Option<Action> = players[seat].react(&uno_match.round.view_for(seat));
+            if let Some(action) = reaction {
+                match uno_match.round.apply(action) {
+                    Ok(_) => {
+                        for event in uno_match.round.drain_events() {
+                            self.broadcast(players, &event.to_string());
+                        }
+                        self.show_spectators(&uno_match.round);
+                    },
+                    Err(err) => players[seat].reject(&err.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Plays a round until someone empties their hand. Every action is checked by the engine,
+    /// and a rejected one is sent back to the player who sent it
+    fn play_round(&mut self, uno_match: &mut Match, players: &mut [RemotePlayer]) {
+        for event in uno_match.round.drain_events() {
+            self.broadcast(players, &event.to_string());
+        }
+        self.show_spectators(&uno_match.round);
+        while uno_match.round.winner().is_none() {
+            let current: usize = uno_match.round.players.current_player;
+            let view: PlayerView = uno_match.round.view_for(current);
+            let action: Action = match choose_command(&mut players[current], &view) {
+                Command::Act(action) => action,
+                _ => view.legal_actions[0],
+            };
+            match uno_match.round.apply(action) {
+                Ok(_) => {
+                    for event in uno_match.round.drain_events() {
+                        self.broadcast(players, &event.to_string());
+                    }
+                    self.show_spectators(&uno_match.round);
+                    self.react(uno_match, players);
+                },
+                Err(_) if players[current].has_left() => {
+                    // The bot that took over is not expected to make mistakes, but must not hold up the round
+                    uno_match.round.apply(view.legal_actions[0]).expect("Expected legal actions to apply");
+                },
+                Err(err) => players[current].reject(&err.to_string()),
+            }
+
+            for seat in 0..players.len() {
+                if players[seat].has_left() && !players[seat].departure_announced {
+                    players[seat].departure_announced = true;
+                    println!("{} left", players[seat].name);
+                    let text: String = format!("Player {} ({}) left. A bot plays their seat", seat_label(seat), players[seat].name);
+                    self.broadcast(players, &text);
+                }
+            }
+            while let Ok(arrival) = self.arrivals.try_recv() {
+                self.admit(arrival, Some(&uno_match.round));
+            }
+        }
+    }
+}
+
+/// Returns the `uno-spectate` block showing the round to a spectator
+fn spectate_message(round: &GameState, show_hands: bool) -> String {
+    let mut writer: SaveWriter = SaveWriter::with_header(SPECTATE_HEADER, SERVER_VERSION);
+    round.spectator_view(show_hands).write_view(&mut writer);
+    format!("{}end", writer.finish())
+}
+
+/// Reads the options after `uno serve`
+pub fn parse_server_args(args: &[String]) -> Result<ServerConfig, String> {
+    let mut config: ServerConfig = ServerConfig {
+        port: DEFAULT_PORT,
+        num_players: 2,
+        rules: RuleSet::official(),
+        target_score: DEFAULT_TARGET_SCORE,
+        timeout: DEFAULT_TURN_TIMEOUT,
+        show_hands: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--show-hands" {
+            config.show_hands = true;
+            continue;
+        }
+        let value: &String = match args.next() {
+            Some(x) => x,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+        match arg.as_ref() {
+            "--port" => config.port = value.parse::<u16>().map_err(|_| "--port must be a port number".to_owned())?,
+            "--players" => config.num_players = parse_number(arg, value)?,
+            "--target" => config.target_score = parse_number(arg, value)?,
+            "--timeout" => config.timeout = Duration::from_secs(parse_number(arg, value)?),
+            "--rules" => {
+                for name in value.split(',') {
+                    if config.rules.toggle(name) != Some(true) {
+                        return Err(format!("Unknown or repeated rule \"{}\"", name));
+                    }
+                }
+            },
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    if config.num_players < 2 || config.num_players > MAX_PLAYERS {
+        return Err(format!("There must be between 2 and {} players", MAX_PLAYERS));
+    }
+    if config.target_score == 0 {
+        return Err("Target score must be a positive number".to_owned());
+    }
+    Ok(config)
+}
+
+/// Runs `uno serve`, hosting one match after another
+pub fn run_server(args: &[String]) {
+    let config: ServerConfig = match parse_server_args(args) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", SERVE_USAGE);
+            return;
+        },
+    };
+    let mut server: Server = match Server::bind(config.clone()) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("Could not listen on port {}: {}", config.port, err);
+            return;
+        },
+    };
+    println!("Listening on {} for {} players (Rules: {})", server.local_addr(), config.num_players, config.rules);
+    loop {
+        server.serve_match();
+    }
+}
@@ -0,0 +1,227 @@
+//! `uno simulate` plays many games between bots with nothing printed along the way,
+//! then reports how well each bot did.
+//!
+//! Every game is a single round dealt from its own seed, so a simulation with the same
+//! options gives the same results however many threads it runs on.
+//! With seat rotation on, the bots move one seat along each game, so no bot always goes first
+
+use bots::{DIFFICULTY_NAMES, Difficulty};
+use game_match::round_points;
+use game_state::{GameState, MAX_PLAYERS};
+use options::parse_number;
+use player::{Player, play_game};
+use rules::RuleSet;
+use std::thread;
+
+/// Games that take more actions than this are stopped and counted as unfinished
+const MAX_ACTIONS: usize = 10000;
+/// The z-score of a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+pub const SIMULATE_USAGE: &str = "Usage: uno simulate [--games N] [--bots easy,medium,...] [--seed N] [--threads N] [--rules rule,...] [--no-rotate]";
+
+/// The options for a simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Simulation {
+    /// The bot in each seat at the start. There is one player per bot
+    pub bots: Vec<Difficulty>,
+    pub games: usize,
+    /// The seed of the first game. Each game after it adds one
+    pub seed: u64,
+    pub threads: usize,
+    /// Moves every bot one seat along after each game
+    pub rotate_seats: bool,
+    pub rules: RuleSet,
+}
+
+/// What happened in one game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct GameResult {
+    /// The index into `Simulation::bots` of the winner, if the game finished
+    winner: Option<usize>,
+    points: u32,
+    actions: usize,
+}
+
+/// The totals over every game of a simulation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulationResults {
+    pub games: usize,
+    /// The number of games stopped before anyone won
+    pub unfinished: usize,
+    pub actions: usize,
+    /// The number of games each bot won
+    pub wins: Vec<usize>,
+    /// The points each bot scored from its wins
+    pub points: Vec<u64>,
+}
+
+impl Simulation {
+    /// Returns the number of threads the games are run on: at least one, and no more than there are games
+    pub fn thread_count(&self) -> usize {
+        self.threads.max(1).min(self.games.max(1))
+    }
+
+    /// Runs every game, split between the threads
+    pub fn run(&self) -> SimulationResults {
+        let threads: usize = self.thread_count();
+        let handles: Vec<thread::JoinHandle<Vec<GameResult>>> = (0..threads)
+            .map(|thread| {
+                let simulation: Simulation = self.clone();
+                thread::spawn(move || {
+                    (thread..simulation.games).step_by(threads).map(|game| simulation.play(game)).collect()
+                })
+            })
+            .collect();
+
+        let mut results: SimulationResults = SimulationResults {
+            games: self.games,
+            unfinished: 0,
+            actions: 0,
+            wins: vec!(0; self.bots.len()),
+            points: vec!(0; self.bots.len()),
+        };
+        for handle in handles {
+            for game in handle.join().expect("Expected the simulation thread not to panic") {
+                results.actions += game.actions;
+                match game.winner {
+                    Some(bot) => {
+                        results.wins[bot] += 1;
+                        results.points[bot] += game.points as u64;
+                    },
+                    None => results.unfinished += 1,
+                }
+            }
+        }
+        results
+    }
+
+    /// Plays the game with the given number
+    fn play(&self, game: usize) -> GameResult {
+        let num_players: usize = self.bots.len();
+        let seed: u64 = self.seed.wrapping_add(game as u64);
+        let rotation: usize = if self.rotate_seats { game % num_players } else { 0 };
+        // Bot i sits in seat (i + rotation) % num_players
+        let seat_bots: Vec<usize> = (0..num_players).map(|seat| (seat + num_players - rotation) % num_players).collect();
+        let mut players: Vec<Box<dyn Player>> = seat_bots.iter()
+            .map(|&bot| self.bots[bot].bot(seed ^ (bot as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            .collect();
+        let mut game_state: GameState = GameState::with_seed(num_players, seed, self.rules)
+            .expect("Expected the number of bots to have been checked with the options");
+        let actions: usize = play_game(&mut game_state, &mut players, MAX_ACTIONS);
+        GameResult {
+            winner: game_state.winner().map(|seat| seat_bots[seat]),
+            points: game_state.winner().map_or(0, |seat| round_points(&game_state, seat)),
+            actions: actions,
+        }
+    }
+}
+
+impl SimulationResults {
+    /// Returns the fraction of games the bot won
+    pub fn win_rate(&self, bot: usize) -> f64 {
+        self.wins[bot] as f64 / self.games as f64
+    }
+
+    /// Returns the 95% Wilson score interval of the bot's win rate
+    pub fn confidence_interval(&self, bot: usize) -> (f64, f64) {
+        let n: f64 = self.games as f64;
+        let p: f64 = self.win_rate(bot);
+        let z2: f64 = CONFIDENCE_Z * CONFIDENCE_Z;
+        let center: f64 = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let spread: f64 = CONFIDENCE_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        ((center - spread).max(0.0), (center + spread).min(1.0))
+    }
+
+    /// Returns the average points the bot scored per game, counting games it lost as 0
+    pub fn points_per_game(&self, bot: usize) -> f64 {
+        self.points[bot] as f64 / self.games as f64
+    }
+
+    /// Returns the average number of actions per game
+    pub fn average_length(&self) -> f64 {
+        self.actions as f64 / self.games as f64
+    }
+}
+
+/// Reads the options after `uno simulate`
+pub fn parse_simulation_args(args: &[String]) -> Result<Simulation, String> {
+    let mut simulation: Simulation = Simulation {
+        bots: vec!(Difficulty::Easy, Difficulty::Medium, Difficulty::Hard),
+        games: 1000,
+        seed: 0,
+        threads: thread::available_parallelism().map(|x| x.get()).unwrap_or(1),
+        rotate_seats: true,
+        rules: RuleSet::official(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--no-rotate" {
+            simulation.rotate_seats = false;
+            continue;
+        }
+        let value: &String = match args.next() {
+            Some(x) => x,
+            None => return Err(format!("{} needs a value", arg)),
+        };
+        match arg.as_ref() {
+            "--games" => simulation.games = parse_number(arg, value)?,
+            "--seed" => simulation.seed = parse_number(arg, value)?,
+            "--threads" => simulation.threads = parse_number(arg, value)?,
+            "--bots" => {
+                simulation.bots = Vec::new();
+                for name in value.split(',') {
+                    match Difficulty::from_name(name) {
+                        Some(x) => simulation.bots.push(x),
+                        None => return Err(format!("Bots must be {}", DIFFICULTY_NAMES.join(", "))),
+                    }
+                }
+            },
+            "--rules" => {
+                for name in value.split(',') {
+                    if simulation.rules.toggle(name) != Some(true) {
+                        return Err(format!("Unknown or repeated rule \"{}\"", name));
+                    }
+                }
+            },
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    if simulation.games == 0 {
+        return Err("There must be at least one game".to_owned());
+    }
+    if simulation.bots.len() < 2 || simulation.bots.len() > MAX_PLAYERS {
+        return Err(format!("There must be between 2 and {} bots", MAX_PLAYERS));
+    }
+    Ok(simulation)
+}
+
+/// Runs `uno simulate` and prints the report
+pub fn run_simulation(args: &[String]) {
+    let simulation: Simulation = match parse_simulation_args(args) {
+        Ok(x) => x,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!("{}", SIMULATE_USAGE);
+            return;
+        },
+    };
+    let results: SimulationResults = simulation.run();
+    println!("Played {} games between {} bots (seed {}, {} threads, seats {})",
+        results.games, simulation.bots.len(), simulation.seed, simulation.thread_count(),
+        if simulation.rotate_seats { "rotated" } else { "fixed" });
+    println!("Rules: {}", simulation.rules);
+    println!("Average game length: {:.1} actions", results.average_length());
+    if results.unfinished > 0 {
+        println!("Unfinished games: {}", results.unfinished);
+    }
+    // Bots move between seats when seats are rotated, so each row is numbered by bot
+    println!("{:<6} {:<10} {:>6} {:>9} {:>14} {:>12}", "Bot", "Difficulty", "Wins", "Win rate", "95% interval", "Points/game");
+    for (bot, difficulty) in simulation.bots.iter().enumerate() {
+        let (low, high) = results.confidence_interval(bot);
+        let interval: String = format!("{:.1}-{:.1}%", low * 100.0, high * 100.0);
+        println!("{:<6} {:<10} {:>6} {:>8.1}% {:>14} {:>12.1}",
+            bot + 1, difficulty.to_string(), results.wins[bot], results.win_rate(bot) * 100.0,
+            interval, results.points_per_game(bot));
+    }
+}
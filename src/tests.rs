@@ -1,7 +1,27 @@
 #[cfg(test)]
 mod tests {
+	extern crate crossterm;
+
+	use self::crossterm::event::KeyCode;
+	use bots::*;
 	use cards::*;
+	use events::*;
+	use external::*;
+	use game_match::*;
 	use game_state::*;
+	use history::*;
+	use mcts::*;
+	use options::*;
+	use player::*;
+	use player_view::*;
+	use read_stdin::*;
+	use replay::*;
+	use rules::*;
+	use save::*;
+	use server::*;
+	use simulate::*;
+	use std::time::Duration;
+	use tui::*;
 
 	macro_rules! panic_on_err {
 		($result: expr) => {
@@ -38,36 +58,36 @@ mod tests {
 		// Play same color onto another
 		let card: Card = Card {color: Color::Red, card_type: CardType::Number(6)};
 		let onto: Card = Card {color: Color::Red, card_type: CardType::Skip};
-		assert!(playable_card(card, onto));
+		assert!(playable_card(&card, &onto));
 
 		// Play same card type onto another
 		let card = Card {color: Color::Red, card_type: CardType::Skip};
 		let onto = Card {color: Color::Blue, card_type: CardType::Skip};
-		assert!(playable_card(card, onto));
+		assert!(playable_card(&card, &onto));
 
 		// Play a wild card onto another
 		let card = Card {color: Color::Any, card_type: CardType::Wild(Color::Any)};
 		let onto = Card {color: Color::Blue, card_type: CardType::Skip};
-		assert!(playable_card(card, onto));
+		assert!(playable_card(&card, &onto));
 
 		// Play a card onto another wild card of the same color
 		let card = Card {color: Color::Red, card_type: CardType::Reverse};
 		let onto = Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Red)};
-		assert!(playable_card(card, onto));
+		assert!(playable_card(&card, &onto));
 
 		// Cannot play a card onto another wild card of a different color
 		let card = Card {color: Color::Red, card_type: CardType::Reverse};
 		let onto = Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Blue)};
-		assert!(!playable_card(card, onto));
+		assert!(!playable_card(&card, &onto));
 
 		// Cannot play a card that shares nothing with another card
 		let card = Card {color: Color::Red, card_type: CardType::Number(6)};
 		let onto = Card {color: Color::Blue, card_type: CardType::Skip};
-		assert!(!playable_card(card, onto));
+		assert!(!playable_card(&card, &onto));
 
 		let card = Card {color: Color::Red, card_type: CardType::Number(5)};
 		let onto = Card {color: Color::Any, card_type: CardType::Wild(Color::Red)};
-		assert!(playable_card(card, onto));
+		assert!(playable_card(&card, &onto));
 	}
 
 	#[test]
@@ -93,12 +113,1236 @@ mod tests {
 
 	#[test]
 	fn test_game_state() {
-		let mut game_state: GameState = GameState {
- 	   		turn_order: TurnOrder::Normal,
-	    	players: Players::new(0),
-	    	draw_deck: Vec::new(),
-    		play_deck: Vec::new(),
-    	};
+		let mut rules: RuleSet = RuleSet::official();
+		rules.plain_starting_card = true;
+		let game_state: GameState = GameState::new(4, rules).unwrap();
+		assert_eq!(game_state.turn_order, TurnOrder::Normal);
+		assert_eq!(game_state.players.get_current_player().len(), 7);
+
+		// Every hand and the starting card must come from one deck
+		assert!(GameState::new(MAX_PLAYERS, rules).is_ok());
+		assert_eq!(GameState::new(MAX_PLAYERS + 1, rules).err(), Some(RuleError::PlayerCount));
+		assert_eq!(GameState::new(0, rules).err(), Some(RuleError::PlayerCount));
+	}
+
+	#[test]
+	fn test_legal_actions() {
+		let play = |code: &str, index: usize| Action::Play(Card::from_code(code).unwrap(), index);
+		let hand: Vec<Card> = cards(&["r3", "b5", "wild", "g7", "b3"]);
+		let red_five: Card = Card::from_code("r5").unwrap();
+
+		// Cards matching the color or number, and wild cards once for each color, then drawing
+		let game_state: GameState = GameState::with_hands(vec!(hand.clone(), cards(&["y1"])), red_five, RuleSet::official());
+		assert_eq!(game_state.legal_actions(), vec!(
+			play("r3", 0), play("b5", 1), play("wild:r", 2), play("wild:g", 2), play("wild:b", 2), play("wild:y", 2), Action::Draw,
+		));
+
+		// Drawing is only allowed with nothing to play under forced_play
+		let mut rules: RuleSet = RuleSet::official();
+		rules.forced_play = true;
+		let game_state: GameState = GameState::with_hands(vec!(hand, cards(&["y1"])), red_five, rules);
+		assert_eq!(game_state.legal_actions(), vec!(
+			play("r3", 0), play("b5", 1), play("wild:r", 2), play("wild:g", 2), play("wild:b", 2), play("wild:y", 2),
+		));
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["g7", "b3"]), cards(&["y1"])), red_five, rules);
+		assert_eq!(game_state.legal_actions(), vec!(Action::Draw));
+	}
+
+	#[test]
+	fn test_apply() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_skip: Card = Card {color: Color::Blue, card_type: CardType::Skip};
+		let wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Any)};
+		let hands: Vec<Vec<Card>> = vec!(vec!(red_five, blue_skip, wild), vec!(red_five));
+		let mut game_state: GameState = GameState::with_hands(hands, red_five, RuleSet::official());
+
+		assert_eq!(game_state.apply(Action::Play(red_five, 3)), Err(RuleError::NotInHand));
+		assert_eq!(game_state.apply(Action::Play(red_five, 1)), Err(RuleError::NotInHand));
+		assert_eq!(game_state.apply(Action::Play(blue_skip, 1)), Err(RuleError::NotPlayable));
+		assert_eq!(game_state.apply(Action::Play(wild, 2)), Err(RuleError::WildColorMissing));
+		assert_eq!(game_state.players.get_current_player().len(), 3);
+
+		let green_wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Green)};
+		assert_eq!(game_state.apply(Action::Play(green_wild, 2)), Ok(Outcome::Continue));
+		assert_eq!(*game_state.top_card(), green_wild);
+		assert_eq!(game_state.players.current_player, 1);
+
+		// Player 2 cannot play red onto green, so they draw
+		assert_eq!(game_state.apply(Action::Play(red_five, 0)), Err(RuleError::NotPlayable));
+		assert_eq!(game_state.apply(Action::Draw), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.current_player, 0);
+
+		assert_eq!(game_state.apply(Action::Play(red_five, 0)), Err(RuleError::NotPlayable));
+		let yellow_wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Yellow)};
+		game_state = GameState::with_hands(vec!(vec!(wild), vec!(red_five)), blue_skip, RuleSet::official());
+		assert_eq!(game_state.apply(Action::Play(yellow_wild, 0)), Ok(Outcome::Won(0)));
+		assert_eq!(game_state.winner(), Some(0));
+		assert_eq!(game_state.apply(Action::Draw), Err(RuleError::GameOver));
+	}
+
+	#[test]
+	fn test_seeded_games_are_reproducible() {
+		let mut game_a: GameState = GameState::with_seed(4, 1234, RuleSet::official()).unwrap();
+		let mut game_b: GameState = GameState::with_seed(4, 1234, RuleSet::official()).unwrap();
+		assert_eq!(game_a, game_b);
+		assert_eq!(game_a.seed(), 1234);
+
+		for _ in 0..200 {
+			let action: Action = game_a.legal_actions()[0];
+			let result = game_a.apply(action);
+			assert_eq!(result, game_b.apply(action));
+			assert_eq!(game_a, game_b);
+			if result != Ok(Outcome::Continue) {
+				break;
+			}
+		}
+
+		assert!(GameState::with_seed(4, 1234, RuleSet::official()).unwrap() != GameState::with_seed(4, 4321, RuleSet::official()).unwrap());
+	}
+
+	#[test]
+	fn test_rule_set_toggle() {
+		let mut rules: RuleSet = RuleSet::official();
+		for name in RULE_NAMES.iter() {
+			assert_eq!(rules.get(name), Some(false));
+			assert_eq!(rules.toggle(name), Some(true));
+			assert_eq!(rules.get(name), Some(true));
+		}
+		assert_eq!(rules.toggle("no-such-rule"), None);
+		assert!(rules.stacking && rules.draw_until_playable && rules.play_drawn_card);
+		assert!(rules.forced_play && rules.seven_zero && rules.jump_in);
+	}
+
+	#[test]
+	fn test_stacking() {
+		let red_plus2: Card = Card {color: Color::Red, card_type: CardType::Plus2};
+		let blue_plus2: Card = Card {color: Color::Blue, card_type: CardType::Plus2};
+		let green_five: Card = Card {color: Color::Green, card_type: CardType::Number(5)};
+		let mut rules: RuleSet = RuleSet::official();
+		rules.stacking = true;
+		let hands: Vec<Vec<Card>> = vec!(
+			vec!(red_plus2, green_five),
+			vec!(blue_plus2, green_five),
+			vec!(green_five, green_five),
+		);
+		let mut game_state: GameState = GameState::with_hands(hands, red_plus2, rules);
+
+		assert_eq!(game_state.apply(Action::Play(red_plus2, 0)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.current_player, 1);
+		assert_eq!(game_state.pending(), Some(Pending::DrawPenalty(2)));
+		assert_eq!(game_state.legal_actions(), vec!(Action::Play(blue_plus2, 0), Action::Draw));
+		assert_eq!(game_state.apply(Action::Play(green_five, 1)), Err(RuleError::NotPlayable));
+
+		assert_eq!(game_state.apply(Action::Play(blue_plus2, 0)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.current_player, 2);
+		assert_eq!(game_state.pending(), Some(Pending::DrawPenalty(4)));
+		assert_eq!(game_state.apply(Action::Draw), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(2).len(), 6);
+		assert_eq!(game_state.players.current_player, 0);
+		assert_eq!(game_state.pending(), None);
+	}
+
+	#[test]
+	fn test_forced_play_and_play_drawn_card() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let mut rules: RuleSet = RuleSet::official();
+		rules.forced_play = true;
+		let hands: Vec<Vec<Card>> = vec!(vec!(red_five, blue_six), vec!(blue_six, blue_six));
+		let mut game_state: GameState = GameState::with_hands(hands.clone(), red_five, rules);
+		assert_eq!(game_state.legal_actions(), vec!(Action::Play(red_five, 0)));
+		assert_eq!(game_state.apply(Action::Draw), Err(RuleError::MustPlay));
+
+		rules.forced_play = false;
+		rules.play_drawn_card = true;
+		rules.draw_until_playable = true;
+		let mut game_state: GameState = GameState::with_hands(hands, blue_six, rules);
+		game_state.apply(Action::Play(blue_six, 1)).unwrap();
+		assert_eq!(game_state.apply(Action::Pass), Err(RuleError::NotAllowed));
+		assert_eq!(game_state.apply(Action::Draw), Ok(Outcome::Continue));
+
+		// Player 2 keeps drawing until the drawn card is playable, and may then play or pass
+		let index: usize = game_state.players.get_current_player().len() - 1;
+		assert_eq!(game_state.players.current_player, 1);
+		assert_eq!(game_state.pending(), Some(Pending::DrawnCard(index)));
+		let drawn: Card = game_state.players.get_current_player()[index];
+		assert!(playable_card(&drawn, game_state.top_card()));
+		assert!(game_state.legal_actions().contains(&Action::Pass));
+		assert_eq!(game_state.apply(Action::Play(blue_six, 0)), Err(RuleError::NotPlayable));
+		assert_eq!(game_state.apply(Action::Pass), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.current_player, 0);
+	}
+
+	#[test]
+	fn test_seven_zero() {
+		let red_seven: Card = Card {color: Color::Red, card_type: CardType::Number(7)};
+		let red_zero: Card = Card {color: Color::Red, card_type: CardType::Number(0)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let green_six: Card = Card {color: Color::Green, card_type: CardType::Number(6)};
+		let mut rules: RuleSet = RuleSet::official();
+		rules.seven_zero = true;
+		let hands: Vec<Vec<Card>> = vec!(
+			vec!(red_seven, red_zero, blue_six),
+			vec!(blue_six),
+			vec!(green_six, green_six),
+		);
+		let mut game_state: GameState = GameState::with_hands(hands, red_seven, rules);
+
+		game_state.apply(Action::Play(red_seven, 0)).unwrap();
+		assert_eq!(game_state.pending(), Some(Pending::SwapHands));
+		assert_eq!(game_state.legal_actions(), vec!(Action::SwapHands(1), Action::SwapHands(2)));
+		assert_eq!(game_state.apply(Action::SwapHands(0)), Err(RuleError::NoSuchPlayer));
+		assert_eq!(game_state.apply(Action::SwapHands(2)), Ok(Outcome::Continue));
+		assert_eq!(*game_state.players.get_player(0), vec!(green_six, green_six));
+		assert_eq!(*game_state.players.get_player(2), vec!(red_zero, blue_six));
+		assert_eq!(game_state.players.current_player, 1);
+
+		// Hands pass along in the direction of play when a zero is played
+		game_state.apply(Action::Draw).unwrap();
+		game_state.apply(Action::Play(red_zero, 0)).unwrap();
+		assert_eq!(*game_state.players.get_player(0), vec!(blue_six));
+		assert_eq!(*game_state.players.get_player(1), vec!(green_six, green_six));
+		assert_eq!(game_state.players.get_player(2).len(), 2);
+		assert_eq!(game_state.players.current_player, 0);
+	}
+
+	#[test]
+	fn test_jump_in() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let hands: Vec<Vec<Card>> = vec!(
+			vec!(blue_six, blue_six),
+			vec!(blue_six, blue_six),
+			vec!(blue_six, red_five, red_five),
+		);
+		let mut game_state: GameState = GameState::with_hands(hands.clone(), red_five, RuleSet::official());
+		assert_eq!(game_state.jump_in_actions(2), vec!());
+		assert_eq!(game_state.apply(Action::JumpIn(2, 1)), Err(RuleError::NotAllowed));
+
+		let mut rules: RuleSet = RuleSet::official();
+		rules.jump_in = true;
+		let mut game_state: GameState = GameState::with_hands(hands, red_five, rules);
+		assert_eq!(game_state.jump_in_actions(1), vec!());
+		assert_eq!(game_state.jump_in_actions(2), vec!(Action::JumpIn(2, 1), Action::JumpIn(2, 2)));
+		assert_eq!(game_state.apply(Action::JumpIn(1, 0)), Err(RuleError::NotPlayable));
+		assert_eq!(game_state.apply(Action::JumpIn(2, 1)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(2).len(), 2);
+		// Play continues from the player who jumped in
+		assert_eq!(game_state.players.current_player, 0);
+
+		// The interrupted player's UNO call does not count for the player who jumped in
+		let hands: Vec<Vec<Card>> = vec!(vec!(blue_six, blue_six), vec!(blue_six), vec!(red_five, blue_six));
+		let mut game_state: GameState = GameState::with_hands(hands, red_five, rules);
+		game_state.apply(Action::CallUno).unwrap();
+		assert_eq!(game_state.apply(Action::JumpIn(2, 0)), Ok(Outcome::Continue));
+		assert_eq!(game_state.uno_vulnerable(), Some(2));
+	}
+
+	#[test]
+	fn test_wild_plus4_challenge() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let plus4: Card = Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Blue)};
+		let honest: Vec<Vec<Card>> = vec!(vec!(plus4, blue_six), vec!(blue_six), vec!(blue_six));
+		let bluffing: Vec<Vec<Card>> = vec!(vec!(plus4, red_five), vec!(blue_six), vec!(blue_six));
+
+		// Accepting draws 4 and skips the player
+		let mut game_state: GameState = GameState::with_hands(honest.clone(), red_five, RuleSet::official());
+		game_state.apply(Action::Play(plus4, 0)).unwrap();
+		assert_eq!(game_state.pending(), Some(Pending::Challenge(0)));
+		assert_eq!(game_state.legal_actions(), vec!(Action::Challenge(true), Action::Challenge(false)));
+		assert_eq!(game_state.apply(Action::Draw), Err(RuleError::NotAllowed));
+		assert_eq!(game_state.apply(Action::Challenge(false)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(1).len(), 5);
+		assert_eq!(game_state.players.current_player, 2);
+
+		// A failed challenge draws 6 and skips the challenger
+		let mut game_state: GameState = GameState::with_hands(honest, red_five, RuleSet::official());
+		game_state.apply(Action::Play(plus4, 0)).unwrap();
+		assert_eq!(game_state.apply(Action::Challenge(true)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(1).len(), 7);
+		assert_eq!(game_state.players.current_player, 2);
+
+		// A successful challenge makes the bluffer draw 4, and the challenger plays on
+		let mut game_state: GameState = GameState::with_hands(bluffing, red_five, RuleSet::official());
+		game_state.apply(Action::Play(plus4, 0)).unwrap();
+		assert_eq!(game_state.pending(), Some(Pending::Challenge(0)));
+		assert_eq!(game_state.apply(Action::Challenge(true)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(0).len(), 5);
+		assert_eq!(game_state.players.get_player(1).len(), 1);
+		assert_eq!(game_state.players.current_player, 1);
+		assert_eq!(game_state.pending(), None);
+	}
+
+	#[test]
+	fn test_uno_call() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let hands: Vec<Vec<Card>> = vec!(vec!(red_five, blue_six), vec!(blue_six, blue_six, blue_six));
+
+		// Players who call UNO cannot be caught
+		let mut game_state: GameState = GameState::with_hands(hands.clone(), red_five, RuleSet::official());
+		assert_eq!(game_state.apply(Action::CallUno), Ok(Outcome::Continue));
+		game_state.apply(Action::Play(red_five, 0)).unwrap();
+		assert_eq!(game_state.uno_vulnerable(), None);
+		assert_eq!(game_state.apply(Action::Catch(1, 0)), Err(RuleError::NotAllowed));
+		assert_eq!(game_state.apply(Action::CallUno), Err(RuleError::NotAllowed));
+
+		// Players who don't may be caught before the next action
+		let mut game_state: GameState = GameState::with_hands(hands.clone(), red_five, RuleSet::official());
+		game_state.apply(Action::Play(red_five, 0)).unwrap();
+		assert_eq!(game_state.uno_vulnerable(), Some(0));
+		assert_eq!(game_state.apply(Action::Catch(0, 1)), Err(RuleError::NotAllowed));
+		// Nobody may catch themselves
+		assert_eq!(game_state.apply(Action::Catch(0, 0)), Err(RuleError::NotAllowed));
+		assert_eq!(game_state.apply(Action::Catch(2, 0)), Err(RuleError::NoSuchPlayer));
+		assert_eq!(game_state.apply(Action::Catch(1, 0)), Ok(Outcome::Continue));
+		assert_eq!(game_state.players.get_player(0).len(), 3);
+		assert_eq!(game_state.players.current_player, 1);
+		assert_eq!(game_state.apply(Action::Catch(1, 0)), Err(RuleError::NotAllowed));
+
+		// But not after it
+		let mut game_state: GameState = GameState::with_hands(hands, red_five, RuleSet::official());
+		game_state.apply(Action::Play(red_five, 0)).unwrap();
+		game_state.apply(Action::Draw).unwrap();
+		assert_eq!(game_state.uno_vulnerable(), None);
+		assert_eq!(game_state.apply(Action::Catch(1, 0)), Err(RuleError::NotAllowed));
+	}
+
+	#[test]
+	fn test_card_points() {
+		let deck_points: u32 = Card::new().map(|card| card.points()).sum();
+		// One of each card: 4 colors of 0-9 and three 20 point cards, then both kinds of wild
+		assert_eq!(deck_points, 4 * (45 + 3 * 20) + 2 * 50);
+		assert_eq!(Card {color: Color::Red, card_type: CardType::Number(7)}.points(), 7);
+		assert_eq!(Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Red)}.points(), 50);
+	}
+
+	#[test]
+	fn test_match() {
+		let mut uno_match: Match = Match::with_seed(3, 99, RuleSet::official(), 100).unwrap();
+		assert_eq!(uno_match.dealer(), 0);
+		assert_eq!(uno_match.round.players.current_player, 1);
+
+		let mut rounds: usize = 0;
+		while uno_match.winner().is_none() {
+			while uno_match.round.winner().is_none() {
+				let action: Action = uno_match.round.legal_actions()[0];
+				uno_match.round.apply(action).unwrap();
+			}
+			let winner: usize = uno_match.round.winner().unwrap();
+			let before: u32 = uno_match.scores()[winner];
+			let points: u32 = round_points(&uno_match.round, winner);
+			assert_eq!(uno_match.score_round(), points);
+			assert_eq!(uno_match.scores()[winner], before + points);
+
+			uno_match.next_round();
+			rounds += 1;
+			assert_eq!(uno_match.round_number(), rounds);
+			assert_eq!(uno_match.dealer(), rounds % 3);
+			assert_eq!(uno_match.round.players.current_player, (rounds + 1) % 3);
+		}
+		assert!(uno_match.scores()[uno_match.winner().unwrap()] >= 100);
+	}
+
+	#[test]
+	fn test_starting_card_effects() {
+		let red_skip: Card = Card {color: Color::Red, card_type: CardType::Skip};
+		let red_reverse: Card = Card {color: Color::Red, card_type: CardType::Reverse};
+		let red_plus2: Card = Card {color: Color::Red, card_type: CardType::Plus2};
+		let wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Any)};
+		let plus4: Card = Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Any)};
+
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(red_skip), RuleSet::official());
+		assert_eq!(game_state.players.current_player, 1);
+
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(red_reverse), RuleSet::official());
+		assert_eq!(game_state.turn_order, TurnOrder::Reverse);
+		assert_eq!(game_state.players.current_player, 2);
+
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(red_plus2), RuleSet::official());
+		assert_eq!(game_state.players.get_player(0).len(), 9);
+		assert_eq!(game_state.players.current_player, 1);
+
+		let mut game_state: GameState = GameState::with_starting_cards(3, vec!(wild), RuleSet::official());
+		assert_eq!(game_state.pending(), Some(Pending::ChooseColor));
+		assert_eq!(game_state.apply(Action::Draw), Err(RuleError::NotAllowed));
+		assert_eq!(game_state.apply(Action::ChooseColor(Color::Any)), Err(RuleError::WildColorMissing));
+		assert_eq!(game_state.apply(Action::ChooseColor(Color::Blue)), Ok(Outcome::Continue));
+		assert_eq!(game_state.top_card().card_type, CardType::Wild(Color::Blue));
+		assert_eq!(game_state.players.current_player, 0);
+
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(plus4, plus4), RuleSet::official());
+		assert!(game_state.top_card().card_type != CardType::WildPlus4(Color::Any));
+
+		// The plain starting card rule ignores effects and never starts with a wild
+		let mut rules: RuleSet = RuleSet::official();
+		rules.plain_starting_card = true;
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(red_plus2), rules);
+		assert_eq!(game_state.players.get_player(0).len(), 7);
+		assert_eq!(game_state.players.current_player, 0);
+		let game_state: GameState = GameState::with_starting_cards(3, vec!(wild, red_skip), rules);
+		assert_eq!(*game_state.top_card(), red_skip);
+		assert_eq!(game_state.players.current_player, 0);
+	}
+
+	#[test]
+	fn test_action_cards_by_player_count() {
+		let red_reverse: Card = Card {color: Color::Red, card_type: CardType::Reverse};
+		let red_skip: Card = Card {color: Color::Red, card_type: CardType::Skip};
+		let red_plus2: Card = Card {color: Color::Red, card_type: CardType::Plus2};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+
+		// (number of players, player after a Reverse, player after a Skip, player after a Plus2)
+		let cases: [(usize, usize, usize, usize); 3] = [
+			(2, 0, 0, 0),
+			(3, 2, 2, 2),
+			(10, 9, 2, 2),
+		];
+		for &(num_players, after_reverse, after_skip, after_plus2) in cases.iter() {
+			let hands: Vec<Vec<Card>> = vec!(vec!(red_reverse, red_skip, red_plus2, blue_six); num_players);
+
+			let mut game_state: GameState = GameState::with_hands(hands.clone(), red_reverse, RuleSet::official());
+			game_state.apply(Action::Play(red_reverse, 0)).unwrap();
+			assert_eq!(game_state.turn_order, TurnOrder::Reverse);
+			assert_eq!(game_state.players.current_player, after_reverse, "Reverse with {} players", num_players);
+
+			let mut game_state: GameState = GameState::with_hands(hands.clone(), red_skip, RuleSet::official());
+			game_state.apply(Action::Play(red_skip, 1)).unwrap();
+			assert_eq!(game_state.players.current_player, after_skip, "Skip with {} players", num_players);
+
+			let mut game_state: GameState = GameState::with_hands(hands, red_plus2, RuleSet::official());
+			game_state.apply(Action::Play(red_plus2, 2)).unwrap();
+			assert_eq!(game_state.players.current_player, after_plus2, "Plus2 with {} players", num_players);
+			assert_eq!(game_state.players.get_player(1).len(), 6);
+			assert_eq!(game_state.players.get_player(0).len(), 3);
+		}
+	}
+
+	#[test]
+	fn test_player_view() {
+		let red_five: Card = Card {color: Color::Red, card_type: CardType::Number(5)};
+		let blue_six: Card = Card {color: Color::Blue, card_type: CardType::Number(6)};
+		let wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Green)};
+		let hands: Vec<Vec<Card>> = vec!(vec!(red_five, blue_six), vec!(blue_six), vec!(red_five, red_five, red_five));
+		let game_state: GameState = GameState::with_hands(hands, wild, RuleSet::official());
+
+		let view: PlayerView = game_state.view_for(1);
+		assert_eq!(view.player, 1);
+		assert_eq!(view.hand, vec!(blue_six));
+		assert_eq!(view.hand_sizes, vec!(2, 1, 3));
+		assert_eq!(view.top_card, wild);
+		assert_eq!(view.active_color, Color::Green);
+		assert_eq!(view.discard_pile, vec!(wild));
+		assert_eq!(view.draw_pile_count, game_state.draw_pile_len());
+		assert_eq!(view.current_player, 0);
+		assert!(!view.is_my_turn());
+		assert_eq!(view.legal_actions, vec!());
+
+		let view: PlayerView = game_state.view_for(0);
+		assert!(view.is_my_turn());
+		assert_eq!(view.hand, vec!(red_five, blue_six));
+		assert_eq!(view.legal_actions, game_state.legal_actions());
+	}
+
+	#[test]
+	fn test_event_log() {
+		let red_skip: Card = Card {color: Color::Red, card_type: CardType::Skip};
+		let red_reverse: Card = Card {color: Color::Red, card_type: CardType::Reverse};
+		let red_plus2: Card = Card {color: Color::Red, card_type: CardType::Plus2};
+		let wild: Card = Card {color: Color::Any, card_type: CardType::Wild(Color::Blue)};
+		let hands: Vec<Vec<Card>> = vec!(vec!(red_skip, red_plus2, wild), vec!(red_reverse, red_skip, wild), vec!(red_skip, red_skip));
+		let mut game_state: GameState = GameState::with_hands(hands, red_skip, RuleSet::official());
+
+		game_state.apply(Action::Play(red_skip, 0)).unwrap();
+		assert_eq!(game_state.drain_events(), vec!(GameEvent::CardPlayed(0, red_skip), GameEvent::TurnSkipped(1)));
+		assert_eq!(game_state.events(), &vec!());
+
+		game_state.apply(Action::Play(red_skip, 0)).unwrap();
+		game_state.apply(Action::Play(red_reverse, 0)).unwrap();
+		game_state.apply(Action::Play(red_plus2, 0)).unwrap();
+		game_state.apply(Action::Play(red_skip, 0)).unwrap();
+		assert_eq!(game_state.drain_events(), vec!(
+			GameEvent::CardPlayed(2, red_skip),
+			GameEvent::TurnSkipped(0),
+			GameEvent::CardPlayed(1, red_reverse),
+			GameEvent::DirectionReversed(TurnOrder::Reverse),
+			GameEvent::CardPlayed(0, red_plus2),
+			GameEvent::CardsDrawn(2, 2),
+			GameEvent::TurnSkipped(2),
+			GameEvent::CardPlayed(1, red_skip),
+			GameEvent::TurnSkipped(0),
+		));
+
+		game_state.apply(Action::Draw).unwrap();
+		game_state.apply(Action::Play(wild, 0)).unwrap();
+		assert_eq!(game_state.drain_events(), vec!(
+			GameEvent::CardsDrawn(2, 1),
+			GameEvent::CardPlayed(1, wild),
+			GameEvent::ColorChosen(1, Color::Blue),
+			GameEvent::PlayerWon(1),
+		));
+	}
+
+	#[test]
+	fn test_card_codes() {
+		for card in Card::new() {
+			assert_eq!(Card::from_code(&card.code()), Ok(card));
+		}
+		let chosen: Card = Card {color: Color::Any, card_type: CardType::WildPlus4(Color::Blue)};
+		assert_eq!(chosen.code(), "wild+4:b");
+		assert_eq!(Card::from_code("wild+4:b"), Ok(chosen));
+		assert_eq!(Card::from_code("r5"), Ok(Card {color: Color::Red, card_type: CardType::Number(5)}));
+		panic_on_any_ok!(
+			Card::from_code(""),
+			Card::from_code("r10"),
+			Card::from_code("x5"),
+			Card::from_code("r5:b"),
+			Card::from_code("wild:a"),
+			Card::from_code("gwild")
+		);
+	}
+
+	#[test]
+	fn test_save_and_load() {
+		let mut rules: RuleSet = RuleSet::official();
+		rules.stacking = true;
+		rules.jump_in = true;
+		let mut uno_match: Match = Match::with_seed(3, 7, rules, 200).unwrap();
+		for _ in 0..20 {
+			let action: Action = uno_match.round.legal_actions()[0];
+			uno_match.round.apply(action).unwrap();
+		}
+		uno_match.round.drain_events();
+
+		let text: String = uno_match.save();
+		assert!(text.starts_with("uno-save 1\n"));
+		let loaded: Match = Match::load(&text).unwrap();
+		assert_eq!(loaded, uno_match);
+
+		// A loaded game plays on exactly like the original
+		let mut original: GameState = uno_match.round.clone();
+		let mut loaded: GameState = loaded.round;
+		while original.winner().is_none() {
+			let action: Action = original.legal_actions()[0];
+			assert_eq!(original.apply(action), loaded.apply(action));
+		}
+		assert_eq!(original, loaded);
+	}
+
+	#[test]
+	fn test_load_errors() {
+		let text: String = Match::with_seed(2, 7, RuleSet::official(), 500).unwrap().save();
+		assert_eq!(Match::load("").unwrap_err(), LoadError::NotASave);
+		assert_eq!(Match::load("not a save").unwrap_err(), LoadError::NotASave);
+		assert_eq!(Match::load(&text.replace("uno-save 1", "uno-save 2")).unwrap_err(), LoadError::WrongVersion(2));
+
+		let lines: Vec<&str> = text.lines().collect();
+		assert_eq!(Match::load(&lines[..10].join("\n")).unwrap_err(), LoadError::Missing("current-player"));
+		match Match::load(&text.replace(lines[9], "turn-order sideways")) {
+			Err(LoadError::Invalid(10, _)) => {},
+			x => panic!("{:?}", x),
+		}
+		// Removing a card from a hand leaves an incomplete deck
+		let last_hand: &str = lines.last().unwrap();
+		let short_hand: String = last_hand[..last_hand.rfind(' ').unwrap()].to_owned();
+		match Match::load(&text.replace(last_hand, &short_hand)) {
+			Err(LoadError::Invalid(_, _)) => {},
+			x => panic!("{:?}", x),
+		}
+		match Match::load(&format!("{}extra 1\n", text)) {
+			Err(LoadError::Invalid(_, _)) => {},
+			x => panic!("{:?}", x),
+		}
+		// More hands than a deck can be dealt to
+		let hands_line: usize = lines.iter().position(|line| *line == "hands 2").unwrap() + 1;
+		match Match::load(&text.replace("hands 2", "hands 15")) {
+			Err(LoadError::Invalid(line, _)) => assert_eq!(line, hands_line),
+			x => panic!("{:?}", x),
+		}
+		match Match::load(&text.replace("target 500", "target 0")) {
+			Err(LoadError::Invalid(2, _)) => {},
+			x => panic!("{:?}", x),
+		}
+	}
+
+	#[test]
+	fn test_action_codes() {
+		let actions: Vec<Action> = vec!(
+			Action::Play(Card::from_code("wild+4:g").unwrap(), 3),
+			Action::Play(Card::from_code("b7").unwrap(), 0),
+			Action::Draw,
+			Action::Pass,
+			Action::Challenge(true),
+			Action::ChooseColor(Color::Yellow),
+			Action::SwapHands(2),
+			Action::JumpIn(1, 4),
+			Action::CallUno,
+			Action::Catch(1, 0),
+		);
+		for action in actions {
+			assert_eq!(Action::from_code(&action.code()), Ok(action));
+		}
+		assert_eq!(Action::Play(Card::from_code("r5").unwrap(), 2).code(), "play 2 r5");
+		panic_on_ok!(Action::from_code("play r5"));
+		panic_on_ok!(Action::from_code("draw 2"));
+		panic_on_ok!(Action::from_code("color a"));
+		panic_on_ok!(Action::from_code("jump"));
+	}
+
+	#[test]
+	fn test_replay() {
+		let mut rules: RuleSet = RuleSet::official();
+		rules.seven_zero = true;
+		let uno_match: Match = Match::with_seed(3, 11, rules, 500).unwrap();
+		let mut replay: Replay = uno_match.start_replay();
+		let mut game_state: GameState = uno_match.round.clone();
+		while game_state.winner().is_none() {
+			let action: Action = game_state.legal_actions()[0];
+			game_state.apply(action).unwrap();
+			replay.record(action);
+		}
+
+		let text: String = replay.save();
+		assert!(text.starts_with("uno-replay 1\n"));
+		let loaded: Replay = Replay::load(&text).unwrap();
+		assert_eq!(loaded, replay);
+		let states: Vec<GameState> = loaded.states();
+		assert_eq!(states.len(), replay.actions().len() + 1);
+		assert_eq!(states[0], uno_match.round);
+		assert_eq!(states.last(), Some(&game_state));
+
+		// A replay whose actions cannot be applied is rejected
+		assert_eq!(Replay::load(&uno_match.save()).unwrap_err(), LoadError::NotASave);
+		let lines: Vec<&str> = text.lines().collect();
+		let first_action: usize = lines.iter().position(|line| line.starts_with("action ")).unwrap();
+		match Replay::load(&text.replacen(lines[first_action], "action catch 0 2", 1)) {
+			Err(LoadError::Invalid(line, _)) => assert_eq!(line, first_action + 1),
+			x => panic!("{:?}", x),
+		}
+		// So is one with more players than the deck can be dealt to
+		let players: usize = lines.iter().position(|line| line.starts_with("players ")).unwrap();
+		match Replay::load(&text.replacen(lines[players], "players 15", 1)) {
+			Err(LoadError::Invalid(line, _)) => assert_eq!(line, players + 1),
+			x => panic!("{:?}", x),
+		}
+	}
+
+	#[test]
+	fn test_undo_and_redo() {
+		let mut game_state: GameState = GameState::with_seed(3, 5, RuleSet::official()).unwrap();
+		let mut history: History = History::new(true);
+		assert!(history.undo(&mut game_state).is_none());
+
+		let mut states: Vec<GameState> = vec!(game_state.clone());
+		let mut actions: Vec<Action> = Vec::new();
+		// Each action is pretended to log one line, so the log is as long as the number of actions before it
+		for i in 0..10 {
+			let action: Action = game_state.legal_actions()[0];
+			history.apply(&mut game_state, action, i).unwrap();
+			states.push(game_state.clone());
+			actions.push(action);
+		}
+		// Rejected actions are not remembered
+		panic_on_ok!(history.apply(&mut game_state, Action::Catch(1, 0), 10));
+
+		assert_eq!(history.undo(&mut game_state), Some((actions[9], 9)));
+		assert_eq!(history.undo(&mut game_state), Some((actions[8], 8)));
+		assert_eq!(game_state, states[8]);
+		assert!(history.can_redo());
+		assert_eq!(history.redo(&mut game_state, 8), Some((actions[8], Outcome::Continue)));
+		assert_eq!(game_state, states[9]);
+
+		// A redone action remembers the log length it was redone at
+		assert_eq!(history.undo(&mut game_state), Some((actions[8], 8)));
+		assert_eq!(history.redo(&mut game_state, 8), Some((actions[8], Outcome::Continue)));
+
+		// A new action forgets what could be redone
+		let action: Action = game_state.legal_actions()[0];
+		history.apply(&mut game_state, action, 9).unwrap();
+		assert!(!history.can_redo());
+		assert_eq!(history.undo(&mut game_state), Some((action, 9)));
+		while history.undo(&mut game_state).is_some() {}
+		assert_eq!(game_state, states[0]);
+
+		let mut disabled: History = History::new(false);
+		let action: Action = game_state.legal_actions()[0];
+		disabled.apply(&mut game_state, action, 0).unwrap();
+		assert!(!disabled.can_undo());
+		assert!(disabled.undo(&mut game_state).is_none());
+	}
+
+	/// Always plays the first card in hand, leaving wild colors to `choose_color`
+	struct FirstCardPlayer;
+
+	impl Player for FirstCardPlayer {
+		fn choose_action(&mut self, view: &PlayerView) -> Action {
+			Action::Play(view.hand[0], 0)
+		}
+
+		fn choose_color(&mut self, _view: &PlayerView) -> Color {
+			Color::Blue
+		}
+	}
+
+	#[test]
+	fn test_players() {
+		// Random players only ever choose legal actions
+		let mut rules: RuleSet = RuleSet::official();
+		rules.seven_zero = true;
+		rules.stacking = true;
+		let mut game_state: GameState = GameState::with_seed(4, 21, rules).unwrap();
+		let mut players: Vec<RandomPlayer> = (0..4).map(RandomPlayer::new).collect();
+		while game_state.winner().is_none() {
+			let current: usize = game_state.players.current_player;
+			let view: PlayerView = game_state.view_for(current);
+			match choose_command(&mut players[current], &view) {
+				Command::Act(action) => panic_on_err!(game_state.apply(action)),
+				_ => panic!(),
+			}
+		}
+
+		// Something done once a turn can be done again once a card has been played
+		let mut game_state: GameState = GameState::with_hands(vec!(cards(&["r3", "b1"]), cards(&["b2"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		let mut uno_called: OncePerTurn = OncePerTurn::new();
+		assert!(uno_called.first_time(&game_state.view_for(0)));
+		assert!(!uno_called.first_time(&game_state.view_for(0)));
+		panic_on_err!(game_state.apply(Action::Play(Card::from_code("r3").unwrap(), 0)));
+		assert!(uno_called.first_time(&game_state.view_for(0)));
+
+		// Or once everyone has had a turn, even if they only drew
+		let mut game_state: GameState = GameState::with_hands(vec!(cards(&["b1", "g2"]), cards(&["b2"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		let mut uno_called: OncePerTurn = OncePerTurn::new();
+		assert!(uno_called.first_time(&game_state.view_for(0)));
+		while game_state.turn() < 2 {
+			match game_state.pending() {
+				Some(Pending::DrawnCard(_)) => panic_on_err!(game_state.apply(Action::Pass)),
+				_ => panic_on_err!(game_state.apply(Action::Draw)),
+			}
+		}
+		assert_eq!(game_state.discard_pile().len(), 1);
+		assert!(uno_called.first_time(&game_state.view_for(0)));
+
+		// Wild cards played without a color are given one by choose_color
+		let wild: Card = Card::from_code("wild").unwrap();
+		let game_state: GameState = GameState::with_hands(vec!(vec!(wild), Vec::new()), Card::from_code("r5").unwrap(), RuleSet::official());
+		match choose_command(&mut FirstCardPlayer, &game_state.view_for(0)) {
+			Command::Act(action) => assert_eq!(action, Action::Play(Card::from_code("wild:b").unwrap(), 0)),
+			_ => panic!(),
+		}
+	}
+
+	fn cards(codes: &[&str]) -> Vec<Card> {
+		codes.iter().map(|code| Card::from_code(code).unwrap()).collect()
+	}
+
+	#[test]
+	fn test_stdin_commands() {
+		let hand: Vec<Card> = cards(&["r5", "bskip", "g+2", "wild", "wild+4", "y0"]);
+		let game_state: GameState = GameState::with_hands(vec!(hand.clone(), cards(&["b1"])), Card::from_code("r3").unwrap(), RuleSet::official());
+		let view: PlayerView = game_state.view_for(0);
+		let play = |code: &str, index: usize| Input::Command(Command::Act(Action::Play(Card::from_code(code).unwrap(), index)));
+
+		// Cards can be picked by number or by name
+		assert_eq!(parse_input("2", &view), play("bskip", 1));
+		for name in ["r5", "red 5", "Red5", " R 5 "].iter() {
+			assert_eq!(parse_input(name, &view), play("r5", 0));
+		}
+		assert_eq!(parse_input("blue skip", &view), play("bskip", 1));
+		assert_eq!(parse_input("green draw two", &view), play("g+2", 2));
+		assert_eq!(parse_input("wild g", &view), play("wild:g", 3));
+		assert_eq!(parse_input("wild", &view), play("wild", 3));
+		assert_eq!(parse_input("+4 blue", &view), play("wild+4:b", 4));
+		assert_eq!(parse_input("yellow 0", &view), play("y0", 5));
+		assert_eq!(parse_input("b5", &view), Input::Invalid("You do not have a Blue 5".to_owned()));
+		assert_eq!(parse_input("0", &view), Input::Invalid("Card does not exist!".to_owned()));
+
+		assert_eq!(parse_input("p", &view), Input::Command(Command::Act(Action::Draw)));
+		assert_eq!(parse_input("catch 2", &view), Input::Command(Command::Act(Action::Catch(view.player, 1))));
+		assert_eq!(parse_input("save Games/My Game.txt", &view), Input::Command(Command::Save("Games/My Game.txt".to_owned())));
+		assert_eq!(parse_input("score", &view), Input::Command(Command::Show(Info::Score)));
+		assert_eq!(parse_input("quit", &view), Input::Command(Command::Quit));
+		assert_eq!(parse_input("sort", &view), Input::Sort);
+
+		// Misspelt commands and cards get a suggestion
+		assert_eq!(suggest("hsitory"), Some("history".to_owned()));
+		assert_eq!(suggest("bleu skp"), Some("blue skip".to_owned()));
+		assert_eq!(suggest("xyzzy"), None);
+		// Seats are typed and shown counting from 1
+		assert_eq!((parse_seat("2"), parse_seat(" 1 "), parse_seat("0"), parse_seat("two")), (Some(1), Some(0), None, None));
+		assert_eq!(seat_label(0), 1);
+		// Lowercasing "İ" makes it longer, which must not move where the first word ends
+		match parse_input("İ x", &view) {
+			Input::Invalid(_) => {},
+			_ => panic!(),
+		}
+		match parse_input("sotr", &view) {
+			Input::Invalid(message) => assert!(message.contains("\"sort\"")),
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn test_medium_bot() {
+		let r5: Card = Card::from_code("r5").unwrap();
+		let mut bot: HeuristicPlayer = HeuristicPlayer::new(Difficulty::Medium);
+
+		// Wild cards are kept while something else can be played
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["wild", "r3", "g7"]), cards(&["b1", "b2", "b3"])), r5, RuleSet::official());
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::Play(Card::from_code("r3").unwrap(), 1));
+
+		// A player about to win is skipped
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["r3", "rskip", "g7"]), cards(&["b1"])), r5, RuleSet::official());
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::Play(Card::from_code("rskip").unwrap(), 1));
+
+		// UNO is called once before playing the second to last card
+		let mut game_state: GameState = GameState::with_hands(vec!(cards(&["r3", "g7"]), cards(&["b1", "b2"])), r5, RuleSet::official());
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::CallUno);
+		game_state.apply(Action::CallUno).unwrap();
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::Play(Card::from_code("r3").unwrap(), 0));
+
+		// A player who forgot to call UNO is caught
+		let mut game_state: GameState = GameState::with_hands(vec!(cards(&["r3", "g7"]), cards(&["b1", "b2"])), r5, RuleSet::official());
+		game_state.apply(Action::Play(Card::from_code("r3").unwrap(), 0)).unwrap();
+		assert_eq!(bot.choose_action(&game_state.view_for(1)), Action::Catch(1, 0));
+
+		// Wild cards become the color held the most
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["wild", "b1", "b2", "g3"]), cards(&["b1"])), r5, RuleSet::official());
+		assert_eq!(bot.choose_color(&game_state.view_for(0)), Color::Blue);
+	}
+
+	#[test]
+	fn test_hard_bot() {
+		let hands: Vec<Vec<Card>> = vec!(cards(&["r1", "b2", "b3"]), cards(&["g1", "g2", "y3"]), cards(&["y1", "y2", "y3"]));
+		let mut game_state: GameState = GameState::with_hands(hands, Card::from_code("r5").unwrap(), RuleSet::official());
+		let mut bot: HeuristicPlayer = HeuristicPlayer::new(Difficulty::Hard);
+		game_state.apply(Action::Play(Card::from_code("r1").unwrap(), 0)).unwrap();
+		assert_eq!(bot.react(&game_state.view_for(0)), None);
+		game_state.apply(Action::Draw).unwrap();
+		assert_eq!(bot.react(&game_state.view_for(0)), None);
+		assert!(bot.lacks(1, Color::Red));
+		assert!(!bot.lacks(2, Color::Red));
+
+		// Cards drawn for being caught without calling UNO say nothing about what a player holds
+		let hands: Vec<Vec<Card>> = vec!(cards(&["rskip", "b2"]), cards(&["g1", "g2"]));
+		let mut game_state: GameState = GameState::with_hands(hands, Card::from_code("r5").unwrap(), RuleSet::official());
+		let mut bot: HeuristicPlayer = HeuristicPlayer::new(Difficulty::Hard);
+		game_state.apply(Action::Play(Card::from_code("rskip").unwrap(), 0)).unwrap();
+		assert_eq!(game_state.players.current_player, 0);
+		assert_eq!(bot.react(&game_state.view_for(1)), Some(Action::Catch(1, 0)));
+		game_state.apply(Action::Catch(1, 0)).unwrap();
+		bot.react(&game_state.view_for(1));
+		assert!(!bot.lacks(0, Color::Red));
+	}
+
+	#[test]
+	fn test_bot_games() {
+		let mut rules: RuleSet = RuleSet::official();
+		rules.jump_in = true;
+		for seed in 0..5 {
+			let mut game_state: GameState = GameState::with_seed(3, seed, rules).unwrap();
+			let mut players: Vec<Box<dyn Player>> = vec!(Difficulty::Easy.bot(seed), Difficulty::Medium.bot(seed), Difficulty::Hard.bot(seed));
+			let mut turns: usize = 0;
+			while game_state.winner().is_none() && turns < 10000 {
+				let current: usize = game_state.players.current_player;
+				match choose_command(players[current].as_mut(), &game_state.view_for(current)) {
+					Command::Act(action) => panic_on_err!(game_state.apply(action)),
+					_ => panic!(),
+				}
+				for (player, bot) in players.iter_mut().enumerate() {
+					if player != game_state.players.current_player && game_state.winner().is_none() {
+						if let Some(action) = bot.react(&game_state.view_for(player)) {
+							panic_on_err!(game_state.apply(action));
+						}
+					}
+				}
+				turns += 1;
+			}
+			assert!(game_state.winner().is_some());
+		}
+	}
+
+	#[test]
+	fn test_determinize() {
+		let mut game_state: GameState = GameState::with_seed(3, 8, RuleSet::official()).unwrap();
+		for _ in 0..15 {
+			let action: Action = game_state.legal_actions()[0];
+			game_state.apply(action).unwrap();
+		}
+		let viewer: usize = game_state.players.current_player;
+		let view: PlayerView = game_state.view_for(viewer);
+		let lacks: Vec<Vec<Color>> = vec!(Vec::new(), vec!(Color::Red), vec!(Color::Red));
+		for seed in 0..5 {
+			let sample: GameState = GameState::determinize(&view, &lacks, seed);
+			// The sample looks the same to the viewer, and still holds exactly one deck
+			assert_eq!(sample.view_for(viewer), view);
+			let mut writer: SaveWriter = SaveWriter::new();
+			sample.write_save(&mut writer);
+			let text: String = writer.finish();
+			panic_on_err!(GameState::read_save(&mut SaveReader::new(&text).unwrap()));
+			for (player, lacking) in lacks.iter().enumerate() {
+				if player != viewer && lacking.contains(&Color::Red) {
+					assert!(sample.players.get_player(player).iter().all(|card| card.color != Color::Red));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_mcts_bot() {
+		// The winning card is found
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["r3"]), cards(&["b1", "b2"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		let mut bot: MctsPlayer = MctsPlayer::new(Budget::iterations(100), 1);
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::Play(Card::from_code("r3").unwrap(), 0));
+
+		// A time budget is respected, and only legal actions are chosen
+		let mut game_state: GameState = GameState::with_seed(2, 4, RuleSet::official()).unwrap();
+		let mut players: Vec<Box<dyn Player>> = vec!(
+			Box::new(MctsPlayer::new(Budget::time(Duration::from_millis(2)), 3)),
+			Difficulty::Medium.bot(0),
+		);
+		while game_state.winner().is_none() {
+			let current: usize = game_state.players.current_player;
+			match choose_command(players[current].as_mut(), &game_state.view_for(current)) {
+				Command::Act(action) => panic_on_err!(game_state.apply(action)),
+				_ => panic!(),
+			}
+		}
+	}
+
+	#[test]
+	fn test_simulation() {
+		let args: Vec<String> = ["--games", "12", "--bots", "easy,medium", "--seed", "7", "--threads", "1", "--rules", "stacking"]
+			.iter().map(|x| x.to_string()).collect();
+		let mut simulation: Simulation = parse_simulation_args(&args).unwrap();
+		assert_eq!(simulation.bots, vec!(Difficulty::Easy, Difficulty::Medium));
+		assert_eq!((simulation.games, simulation.seed, simulation.threads), (12, 7, 1));
+		assert!(simulation.rules.stacking);
+		assert!(simulation.rotate_seats);
+
+		// The results do not depend on the number of threads
+		let results: SimulationResults = simulation.run();
+		simulation.threads = 5;
+		assert_eq!(simulation.run(), results);
+		// No more threads are run than there are games
+		simulation.threads = 16;
+		assert_eq!(simulation.thread_count(), 12);
+		simulation.threads = 0;
+		assert_eq!(simulation.thread_count(), 1);
+		assert_eq!(results.wins.iter().sum::<usize>() + results.unfinished, 12);
+		let (low, high) = results.confidence_interval(1);
+		assert!(low <= results.win_rate(1) && results.win_rate(1) <= high);
+
+		for bad in [&["--games", "0"][..], &["--bots", "medium"], &["--bots", "easy,easy,easy,easy,easy,easy,easy,easy,easy,easy,easy,easy,easy,easy,easy"], &["--bots", "nobody,easy"], &["--games", "99999999999999999999"], &["--seed"], &["--frobnicate", "1"]].iter() {
+			let args: Vec<String> = bad.iter().map(|x| x.to_string()).collect();
+			assert!(parse_simulation_args(&args).is_err());
+		}
+	}
+
+	#[test]
+	fn test_game_args() {
+		let args: Vec<String> = ["--seats", "human,hard,expert", "--program", "2", "./bot --fast", "--rules", "jump-in", "--preset", "house",
+			"--seed", "9", "--target", "200", "--color", "off", "--output", "tui", "--no-undo"]
+			.iter().map(|x| x.to_string()).collect();
+		let options: GameOptions = parse_game_args(&args).unwrap();
+		assert_eq!(options.num_players, 3);
+		assert_eq!(options.bot_seats, vec!((2, Bot::BuiltIn(Difficulty::Expert)), (1, Bot::External("./bot --fast".to_owned()))));
+		// House rules go on top of the preset, whichever comes first
+		assert_eq!(options.rules, RuleSet { jump_in: true, ..RuleSet::preset("house").unwrap() });
+		assert_eq!((options.seed, options.target_score), (Some(9), 200));
+		assert!(!options.color && options.full_screen && !options.undo_allowed && options.start);
+		assert_eq!(options.expert_budget(), Budget::iterations(DEFAULT_ITERATIONS));
+
+		// The Expert bot can be given a time to think instead
+		let timed: Vec<String> = vec!("--expert-time".to_owned(), "250".to_owned());
+		assert_eq!(parse_game_args(&timed).unwrap().expert_budget(), Budget::time(Duration::from_millis(250)));
+
+		// Without options the menu is shown
+		assert_eq!(parse_game_args(&[]), Ok(GameOptions::default()));
+		let menu: Vec<String> = vec!("--players".to_owned(), "2".to_owned(), "--menu".to_owned());
+		assert!(!parse_game_args(&menu).unwrap().start);
+
+		for bad in [&["--players", "0"][..], &["--players", "15"], &["--players", "2", "--seats", "human,easy,easy"], &["--seats", "human,nobody"],
+			&["--program", "5", "bot"], &["--preset", "chaos"], &["--color", "maybe"], &["--expert-time", "soon"], &["--seed"], &["--frobnicate", "1"],
+			// Too large to be a target score, rather than wrapped around to 200
+			&["--target", "4294967496"]].iter() {
+			let args: Vec<String> = bad.iter().map(|x| x.to_string()).collect();
+			assert!(parse_game_args(&args).is_err());
+		}
+	}
+
+	/// A shell script bot in a temporary file, which is removed when this is dropped
+	#[cfg(unix)]
+	struct ScriptBot {
+		path: std::path::PathBuf,
+		/// The command that runs the script
+		command: String,
+	}
+
+	#[cfg(unix)]
+	impl Drop for ScriptBot {
+		fn drop(&mut self) {
+			let _ = std::fs::remove_file(&self.path);
+		}
+	}
+
+	/// Writes a shell script bot to a temporary file
+	#[cfg(unix)]
+	fn script_bot(name: &str, script: &str) -> ScriptBot {
+		let path: std::path::PathBuf = std::env::temp_dir().join(format!("uno-test-{}-{}.sh", name, std::process::id()));
+		std::fs::write(&path, script).unwrap();
+		let command: String = format!("sh {}", path.display());
+		ScriptBot { path: path, command: command }
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_external_bot() {
+		let timeout: Duration = Duration::from_secs(5);
+		let play_against_medium = |bot: ExternalPlayer| {
+			let mut game_state: GameState = GameState::with_seed(2, 9, RuleSet::official()).unwrap();
+			let mut players: Vec<Box<dyn Player>> = vec!(Box::new(bot), Difficulty::Medium.bot(0));
+			play_game(&mut game_state, &mut players, 10000);
+			assert!(game_state.winner().is_some());
+		};
+
+		// Plays the first legal action it is sent
+		let first_legal: ScriptBot = script_bot("first-legal", "read hello\necho ready First Legal\nwhile read key rest; do\n\tcase $key in\n\t\tuno-view) first= ;;\n\t\tlegal) [ -z \"$first\" ] && first=$rest ;;\n\t\tgo) echo \"$first\" ;;\n\t\treact) echo none ;;\n\t\tquit) exit ;;\n\tesac\ndone\n");
+		let bot: ExternalPlayer = ExternalPlayer::start(&first_legal.command, timeout, Output::Lines).unwrap();
+		assert_eq!(bot.name(), "First Legal");
+		play_against_medium(bot);
+
+		// Nonsense and illegal replies are replaced without stopping the bot
+		let nonsense: ScriptBot = script_bot("nonsense", "read hello\necho ready\nwhile read key rest; do\n\tcase $key in\n\t\tgo) echo catch 0 0 ;;\n\t\treact) echo banana ;;\n\tesac\ndone\n");
+		let mut bot: ExternalPlayer = ExternalPlayer::start(&nonsense.command, timeout, Output::Lines).unwrap();
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["r3", "b1", "b4"]), cards(&["b2"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		assert_eq!(bot.choose_action(&game_state.view_for(0)), Action::Play(Card::from_code("r3").unwrap(), 0));
+		assert_eq!(bot.failure(), None);
+		play_against_medium(bot);
+
+		// A bot is only asked for a color when a starting Wild needs one
+		let colors: ScriptBot = script_bot("colors", "read hello\necho ready\nwhile read key rest; do\n\tcase $key in\n\t\tgo) echo color y ;;\n\t\tquit) exit ;;\n\tesac\ndone\n");
+		let mut bot: ExternalPlayer = ExternalPlayer::start(&colors.command, timeout, Output::Lines).unwrap();
+		let starting_wild: GameState = GameState::with_starting_cards(2, cards(&["wild"]), RuleSet::official());
+		assert_eq!(starting_wild.pending(), Some(Pending::ChooseColor));
+		assert_eq!(bot.choose_color(&starting_wild.view_for(starting_wild.players.current_player)), Color::Yellow);
+		let wild_in_hand: GameState = GameState::with_hands(vec!(cards(&["wild", "b1", "b2"]), cards(&["g1"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		assert_eq!(bot.choose_color(&wild_in_hand.view_for(0)), Color::Blue);
+		assert_eq!(bot.failure(), None);
+
+		// A bot that exits is replaced for the rest of the game
+		let exits: ScriptBot = script_bot("exits", "read hello\necho ready\n");
+		let mut bot: ExternalPlayer = ExternalPlayer::start(&exits.command, timeout, Output::Lines).unwrap();
+		bot.choose_action(&game_state.view_for(0));
+		assert_eq!(bot.failure(), Some(&ExternalError::Exited));
+		play_against_medium(bot);
+
+		let silent: ScriptBot = script_bot("silent", "read hello\nsleep 5\n");
+		assert_eq!(ExternalPlayer::start(&silent.command, Duration::from_millis(100), Output::Lines).err(), Some(ExternalError::Timeout));
+		let not_ready: ScriptBot = script_bot("not-ready", "read hello\necho hello\n");
+		assert_eq!(ExternalPlayer::start(&not_ready.command, timeout, Output::Lines).err(), Some(ExternalError::NotReady("hello".to_owned())));
+		match ExternalPlayer::start("uno-test-no-such-program", timeout, Output::Lines) {
+			Err(ExternalError::Start(_)) => {},
+			_ => panic!(),
+		}
+	}
+
+	/// Joins a server and plays the first legal action it is sent, after first sending an illegal play.
+	/// Out of turn it jumps in or catches whoever it can. Returns the errors it was sent and the number of reactions it made
+	fn first_legal_client(address: std::net::SocketAddr, name: &str) -> (Vec<String>, usize) {
+		let stream: std::net::TcpStream = std::net::TcpStream::connect(address).unwrap();
+		let mut connection: Connection = Connection::new(stream.try_clone().unwrap(), stream, Duration::from_secs(10));
+		assert_eq!(connection.receive().unwrap(), "uno-server 1");
+		connection.send(&format!("join {}", name)).unwrap();
+		let (mut errors, mut view, mut reactions): (Vec<String>, String, usize) = (Vec::new(), String::new(), 0);
+		loop {
+			let line: String = connection.receive().unwrap();
+			if line == "quit" {
+				return (errors, reactions);
+			} else if line == "react" {
+				let mut reader: SaveReader = SaveReader::with_header(&view, VIEW_HEADER, PROTOCOL_VERSION).unwrap();
+				let player_view: PlayerView = PlayerView::read_view(&mut reader).unwrap();
+				let reply: String = match (player_view.legal_actions.first(), player_view.uno_vulnerable) {
+					(Some(action), _) => action.code(),
+					(None, Some(player)) => Action::Catch(player_view.player, player).code(),
+					(None, None) => panic!("Expected to be asked only when there is something to do"),
+				};
+				connection.send(&reply).unwrap();
+				reactions += 1;
+			} else if line == "go" {
+				let mut reader: SaveReader = SaveReader::with_header(&view, VIEW_HEADER, PROTOCOL_VERSION).unwrap();
+				let player_view: PlayerView = PlayerView::read_view(&mut reader).unwrap();
+				assert_eq!(player_view.hand.len(), player_view.hand_sizes[player_view.player]);
+				let reply: String = if errors.is_empty() { "play 99 r1".to_owned() } else { player_view.legal_actions[0].code() };
+				connection.send(&reply).unwrap();
+			} else if let Some(error) = line.strip_prefix("error ") {
+				errors.push(error.to_owned());
+			} else if line.starts_with(VIEW_HEADER) {
+				view = format!("{}\n", line);
+			} else if line != "end" && !view.is_empty() && !line.starts_with("message ") && !line.starts_with("seat ") {
+				view.push_str(&format!("{}\n", line));
+			}
+		}
+	}
+
+	#[test]
+	fn test_server() {
+		let args: Vec<String> = ["--port", "0", "--players", "3", "--target", "1", "--timeout", "5", "--show-hands", "--rules", "jump-in"].iter().map(|x| x.to_string()).collect();
+		let config: ServerConfig = parse_server_args(&args).unwrap();
+		assert!(config.show_hands);
+		assert!(parse_server_args(&["--players".to_owned(), "1".to_owned()]).is_err());
+		assert!(parse_server_args(&["--players".to_owned(), "15".to_owned()]).is_err());
+		assert!(parse_server_args(&["--target".to_owned(), "4294967297".to_owned()]).is_err());
+		let mut server: Server = Server::bind(config).unwrap();
+		let address: std::net::SocketAddr = ([127, 0, 0, 1], server.local_addr().port()).into();
+
+		// The spectator watches the whole match, and the players join once it is watching
+		let spectator = std::thread::spawn(move || {
+			let stream: std::net::TcpStream = std::net::TcpStream::connect(address).unwrap();
+			let mut connection: Connection = Connection::new(stream.try_clone().unwrap(), stream, Duration::from_secs(10));
+			assert_eq!(connection.receive().unwrap(), "uno-server 1");
+			connection.send("watch Carol").unwrap();
+			assert_eq!(connection.receive().unwrap(), "message You are watching. Every hand is shown");
+			let clients: Vec<std::thread::JoinHandle<(Vec<String>, usize)>> = ["Alice", "Bob", "Dave"].iter()
+				.map(|name| {
+					let name: String = name.to_string();
+					std::thread::spawn(move || first_legal_client(address, &name))
+				})
+				.collect();
+			let (mut views, mut block): (Vec<SpectatorView>, String) = (Vec::new(), String::new());
+			loop {
+				let line: String = connection.receive().unwrap();
+				if line.ends_with("wins the match!") {
+					return (clients, views);
+				} else if line.starts_with(SPECTATE_HEADER) {
+					block = format!("{}\n", line);
+				} else if line == "end" {
+					let mut reader: SaveReader = SaveReader::with_header(&block, SPECTATE_HEADER, SERVER_VERSION).unwrap();
+					views.push(SpectatorView::read_view(&mut reader).unwrap());
+				} else if !line.starts_with("message ") {
+					block.push_str(&format!("{}\n", line));
+				}
+			}
+		});
+
+		let uno_match: Match = server.serve_match();
+		assert!(uno_match.winner().is_some());
+		let (clients, views) = spectator.join().unwrap();
+		let mut reactions: usize = 0;
+		for client in clients {
+			// The illegal play is rejected and asked for again, and every reaction is accepted
+			let (errors, count) = client.join().unwrap();
+			assert_eq!(errors.len(), 1);
+			reactions += count;
+		}
+		// Whoever wins the round was left with one card without calling UNO, while someone else could catch them
+		assert!(reactions > 0);
+		assert!(!views.is_empty());
+		assert!(views.iter().all(|view| view.hands.as_ref().unwrap().iter().map(Vec::len).collect::<Vec<usize>>() == view.hand_sizes));
+	}
+
+	#[test]
+	fn test_view_codes() {
+		// A starting Wild has no color yet
+		let wild: Card = Card::from_code("wild").unwrap();
+		let starting_wild: GameState = GameState::with_starting_cards(3, vec!(wild), RuleSet::official());
+		let game_state: GameState = GameState::with_seed(3, 12, RuleSet::official()).unwrap();
+		for (game_state, player) in [&starting_wild, &game_state].iter().flat_map(|x| (0..3).map(move |player| (x, player))) {
+			let view: PlayerView = game_state.view_for(player);
+			let mut writer: SaveWriter = SaveWriter::with_header(VIEW_HEADER, PROTOCOL_VERSION);
+			view.write_view(&mut writer);
+			let text: String = writer.finish();
+			let mut reader: SaveReader = SaveReader::with_header(&text, VIEW_HEADER, PROTOCOL_VERSION).unwrap();
+			assert_eq!(PlayerView::read_view(&mut reader), Ok(view));
+		}
+
+		for show_hands in [false, true].iter() {
+			let view: SpectatorView = game_state.spectator_view(*show_hands);
+			assert_eq!(view.hands.is_some(), *show_hands);
+			// Without the hands, a spectator sees what any player can see
+			if !*show_hands {
+				assert_eq!(view, game_state.view_for(1).public_view());
+			}
+			let mut writer: SaveWriter = SaveWriter::with_header(SPECTATE_HEADER, SERVER_VERSION);
+			view.write_view(&mut writer);
+			let text: String = writer.finish();
+			let mut reader: SaveReader = SaveReader::with_header(&text, SPECTATE_HEADER, SERVER_VERSION).unwrap();
+			assert_eq!(SpectatorView::read_view(&mut reader), Ok(view));
+		}
+	}
+
+	/// Records what a hand-off asks of the terminal
+	#[derive(Default)]
+	struct RecordedTerminal {
+		calls: std::cell::RefCell<Vec<String>>,
+	}
+
+	impl SharedTerminal for RecordedTerminal {
+		fn hide_hand(&self) {
+			self.calls.borrow_mut().push("hide".to_owned());
+		}
+
+		fn hand_off(&self, view: &PlayerView) {
+			self.calls.borrow_mut().push(format!("hand off to {}", view.player));
+		}
+	}
+
+	#[test]
+	fn test_hand_off() {
+		let game_state: GameState = GameState::with_hands(vec!(cards(&["r1"]), cards(&["r2"]), cards(&["r3"])), Card::from_code("r5").unwrap(), RuleSet::official());
+		let (first, second): (PlayerView, PlayerView) = (game_state.view_for(0), game_state.view_for(1));
+		let terminal: RecordedTerminal = RecordedTerminal::default();
+		let mut hand_off: HandOff = HandOff::new(true, &[true, true, false]);
+		hand_off.before_turn(&terminal, &first);
+		// Asking the same person again, or an action that keeps their turn, changes nothing
+		hand_off.before_turn(&terminal, &first);
+		hand_off.after_action(&terminal, 0, false);
+		assert_eq!(*terminal.calls.borrow(), vec!("hand off to 0"));
+		// The hand is hidden as soon as the turn passes on, and the next one waits for its person
+		hand_off.after_action(&terminal, 1, false);
+		hand_off.before_turn(&terminal, &second);
+		// Undo can go back to the first person with the second hand still showing
+		hand_off.before_turn(&terminal, &first);
+		hand_off.after_action(&terminal, 0, true);
+		assert_eq!(*terminal.calls.borrow(), vec!("hand off to 0", "hide", "hand off to 1", "hide", "hand off to 0", "hide"));
+
+		// Nothing is hidden from a person playing alone, or without privacy
+		for (privacy, humans) in [(true, [true, false, false]), (false, [true, true, true])].iter() {
+			let terminal: RecordedTerminal = RecordedTerminal::default();
+			let mut hand_off: HandOff = HandOff::new(*privacy, humans);
+			hand_off.before_turn(&terminal, &first);
+			hand_off.after_action(&terminal, 1, false);
+			hand_off.before_turn(&terminal, &second);
+			assert!(terminal.calls.borrow().is_empty());
+		}
+	}
+
+	#[test]
+	fn test_tui_layout() {
+		let labels: Vec<String> = ["r5", "gskip", "brev", "y+2", "wild", "wild+4"].iter()
+			.map(|code| card_label(&Card::from_code(code).unwrap()))
+			.collect();
+		assert_eq!(labels, vec!("5", "Skip", "Rev", "+2", "Wild", "+4"));
+
+		// Everything fits
+		assert_eq!(visible_cards(&[4, 4, 4], 0, 20), (0, 3));
+		// Cards to the right of the selection are shown first
+		assert_eq!(visible_cards(&[4, 4, 4, 4, 4], 1, 8), (1, 3));
+		// Then cards to the left fill any space left over
+		assert_eq!(visible_cards(&[4, 4, 4, 4, 4], 4, 12), (2, 5));
+		assert_eq!(visible_cards(&[], 0, 10), (0, 0));
+	}
+
+	#[test]
+	fn test_tui_keys() {
+		let hands: Vec<Vec<Card>> = vec!(cards(&["r3", "b1"]), cards(&["b2", "b4"]));
+		let mut game_state: GameState = GameState::with_hands(hands, Card::from_code("r5").unwrap(), RuleSet::official());
+		assert_eq!(command_for_key(KeyCode::Enter, &game_state.view_for(0), 0), Some(Command::Act(Action::Play(Card::from_code("r3").unwrap(), 0))));
+		assert_eq!(command_for_key(KeyCode::Char('c'), &game_state.view_for(0), 0), None);
 
+		// Only someone else can catch a player who did not call UNO
+		game_state.apply(Action::Play(Card::from_code("r3").unwrap(), 0)).unwrap();
+		assert_eq!(command_for_key(KeyCode::Char('c'), &game_state.view_for(1), 0), Some(Command::Act(Action::Catch(1, 0))));
+		assert_eq!(command_for_key(KeyCode::Char('c'), &game_state.view_for(0), 0), None);
 	}
 }
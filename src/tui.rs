@@ -0,0 +1,531 @@
+extern crate crossterm;
+
+use self::crossterm::{cursor, execute, queue, terminal};
+use self::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use self::crossterm::style::{self, Attribute, Print};
+use cards::{Card, CardType, Color};
+use game_state::{Action, Pending, TurnOrder, WILD_COLORS, playable_card, seat_label};
+use player::{Command, Player};
+use player_view::PlayerView;
+use read_stdin::read_string_from_stdin;
+use std::cell::RefCell;
+use std::io::{self, Stdout, Write};
+use std::rc::Rc;
+
+/// The keys, shown at the bottom of the screen
+const HELP: &str = "←/→ select  Enter play  d draw  p pass  u UNO  c catch  z undo  y redo  s save  l load  PgUp/PgDn log  q quit";
+
+/// Where a local game's messages go
+#[derive(Clone)]
+pub enum Output {
+	/// Printed one line at a time, which suits piping and scripting
+	Lines,
+	/// Added to the event log of the full-screen interface
+	Screen(Rc<RefCell<Screen>>),
+}
+
+impl Output {
+	pub fn say(&self, text: &str) {
+		match *self {
+			Output::Lines => println!("{}", text),
+			Output::Screen(ref screen) => screen.borrow_mut().say(text),
+		}
+	}
+}
+
+/// What passing the terminal from one person to the next needs from where the game is shown
+pub trait SharedTerminal {
+	/// Hides the hand on screen at the end of a person's turn, so the next person cannot see it
+	fn hide_hand(&self);
+
+	/// Waits for the viewer to take the seat before their hand is shown
+	fn hand_off(&self, view: &PlayerView);
+}
+
+impl SharedTerminal for Output {
+	/// Printed lines are cleared from the terminal, scrollback included
+	fn hide_hand(&self) {
+		match *self {
+			Output::Lines => {
+				let _ = execute!(io::stdout(), terminal::Clear(terminal::ClearType::All), terminal::Clear(terminal::ClearType::Purge), cursor::MoveTo(0, 0));
+			},
+			Output::Screen(ref screen) => screen.borrow_mut().hide(),
+		}
+	}
+
+	fn hand_off(&self, view: &PlayerView) {
+		match *self {
+			Output::Lines => {
+				read_string_from_stdin(Some(format!("Pass to player {}, press Enter", seat_label(view.player))));
+			},
+			Output::Screen(ref screen) => screen.borrow_mut().hand_off(view),
+		}
+	}
+}
+
+/// Hides each person's hand between their turns when several people share the terminal.
+/// A hand is hidden once its turn has passed on, and only shown again after the next person
+/// has taken the seat. With one person at the terminal, or privacy turned off, nothing is hidden
+pub struct HandOff {
+	enabled: bool,
+	/// The seat whose hand is on screen
+	shown: Option<usize>,
+}
+
+impl HandOff {
+	/// `humans` holds whether each seat is played by a person at the terminal
+	pub fn new(privacy: bool, humans: &[bool]) -> HandOff {
+		HandOff {
+			enabled: privacy && humans.iter().filter(|x| **x).count() > 1,
+			shown: None,
+		}
+	}
+
+	/// Called before a person takes their turn, with their view
+	pub fn before_turn(&mut self, terminal: &dyn SharedTerminal, view: &PlayerView) {
+		if !self.enabled || self.shown == Some(view.player) {
+			return;
+		}
+		// Undo can go back to another person's turn with a hand still showing
+		if self.shown.is_some() {
+			terminal.hide_hand();
+		}
+		terminal.hand_off(view);
+		self.shown = Some(view.player);
+	}
+
+	/// Called after each action, before what happened is shown, so the next person can read it.
+	/// `current_player` is whose turn it is now
+	pub fn after_action(&mut self, terminal: &dyn SharedTerminal, current_player: usize, round_over: bool) {
+		if self.shown.is_some() && (self.shown != Some(current_player) || round_over) {
+			terminal.hide_hand();
+			self.shown = None;
+		}
+	}
+}
+
+/// What the row at the bottom of the screen is choosing between
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Picker {
+	Hand,
+	Colors,
+	/// The other players, to swap hands with
+	Players,
+}
+
+/// The full-screen interface. The terminal is put back as it was when the Screen is dropped
+pub struct Screen {
+	stdout: Stdout,
+	log: Vec<String>,
+	/// How many lines the log is scrolled back from the newest
+	scroll: usize,
+	/// The last view shown, which stays on screen while the computer takes its turns
+	last_view: Option<PlayerView>,
+	/// The index of the selected card, color or player
+	selected: usize,
+	/// Whether the hand is hidden until the next person takes the seat
+	hidden: bool,
+	/// Whether the terminal is still in full-screen mode
+	active: bool,
+}
+
+impl Screen {
+	/// Switches the terminal to full-screen mode
+	pub fn start() -> io::Result<Screen> {
+		let mut stdout: Stdout = io::stdout();
+		terminal::enable_raw_mode()?;
+		execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+		Ok(Screen {
+			stdout: stdout,
+			log: Vec::new(),
+			scroll: 0,
+			last_view: None,
+			selected: 0,
+			hidden: false,
+			active: true,
+		})
+	}
+
+	/// Puts the terminal back as it was
+	pub fn stop(&mut self) {
+		if self.active {
+			self.active = false;
+			let _ = execute!(self.stdout, cursor::Show, terminal::LeaveAlternateScreen);
+			let _ = terminal::disable_raw_mode();
+		}
+	}
+
+	/// Adds a line to the event log and shows it
+	pub fn say(&mut self, text: &str) {
+		self.log.push(text.to_owned());
+		self.scroll = 0;
+		if let Some(view) = self.last_view.clone() {
+			let _ = self.draw(&view, None, "");
+		}
+	}
+
+	/// The player whose hand is on screen, if anyone's has been shown yet
+	pub fn viewer(&self) -> Option<usize> {
+		self.last_view.as_ref().map(|view| view.player)
+	}
+
+	/// Shows the table while it is not the viewer's turn
+	pub fn show(&mut self, view: PlayerView) {
+		let _ = self.draw(&view, None, "");
+		self.last_view = Some(view);
+	}
+
+	/// Hides the hand on screen
+	pub fn hide(&mut self) {
+		self.hidden = true;
+		if let Some(view) = self.last_view.clone() {
+			let _ = self.draw(&view, None, "");
+		}
+	}
+
+	/// Waits for Enter before the viewer's hand is shown
+	pub fn hand_off(&mut self, view: &PlayerView) {
+		self.hidden = true;
+		self.last_view = Some(view.clone());
+		let prompt: String = format!("Pass to player {}, press Enter", seat_label(view.player));
+		while self.read_key(view, None, &prompt) != KeyCode::Enter {}
+		self.hidden = false;
+	}
+
+	/// Waits for a key before the terminal is put back, so the end of the match can be read
+	pub fn finish(&mut self) {
+		if let Some(view) = self.last_view.clone() {
+			self.read_key(&view, None, "The match is over. Press any key to exit");
+		}
+		self.stop();
+	}
+
+	/// Asks for a command on the viewer's turn. Wild cards are returned without a color
+	pub fn read_command(&mut self, view: &PlayerView) -> Command {
+		self.last_view = Some(view.clone());
+		match view.pending {
+			Some(Pending::ChooseColor) => return Command::Act(Action::ChooseColor(self.read_color(view))),
+			Some(Pending::SwapHands) => return Command::Act(Action::SwapHands(self.read_player(view))),
+			Some(Pending::Challenge(offender)) => {
+				let prompt: String = format!("Player {} played a Wild Plus 4 onto you. Challenge it? (y/n)", seat_label(offender));
+				loop {
+					match self.read_key(view, None, &prompt) {
+						KeyCode::Char('y') => return Command::Act(Action::Challenge(true)),
+						KeyCode::Char('n') => return Command::Act(Action::Challenge(false)),
+						_ => {},
+					}
+				}
+			},
+			_ => {},
+		}
+		self.selected = match view.pending {
+			Some(Pending::DrawnCard(index)) => index,
+			_ => self.selected.min(view.hand.len().saturating_sub(1)),
+		};
+		loop {
+			let key: KeyCode = self.read_key(view, Some(Picker::Hand), HELP);
+			match key {
+				KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+				KeyCode::Right => self.selected = (self.selected + 1).min(view.hand.len().saturating_sub(1)),
+				KeyCode::Char('s') | KeyCode::Char('l') => {
+					let label: &str = if key == KeyCode::Char('s') { "Save to: " } else { "Load from: " };
+					if let Some(path) = self.read_line(view, label) {
+						return if key == KeyCode::Char('s') { Command::Save(path) } else { Command::Load(path) };
+					}
+				},
+				_ => if let Some(command) = command_for_key(key, view, self.selected) {
+					return command;
+				},
+			}
+		}
+	}
+
+	/// Asks for the color of a wild card with a picker
+	pub fn read_color(&mut self, view: &PlayerView) -> Color {
+		self.selected = 0;
+		loop {
+			match self.read_key(view, Some(Picker::Colors), "Choose a color: ←/→ and Enter, or r/g/b/y") {
+				KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+				KeyCode::Right => self.selected = (self.selected + 1).min(WILD_COLORS.len() - 1),
+				KeyCode::Enter | KeyCode::Char(' ') => return WILD_COLORS[self.selected],
+				KeyCode::Char(x) => if let Some(color) = Color::from_code(&x.to_string()).filter(|x| *x != Color::Any) {
+					return color;
+				},
+				_ => {},
+			}
+		}
+	}
+
+	/// Asks which other player to swap hands with
+	fn read_player(&mut self, view: &PlayerView) -> usize {
+		let others: Vec<usize> = (0..view.num_players()).filter(|x| *x != view.player).collect();
+		self.selected = 0;
+		loop {
+			match self.read_key(view, Some(Picker::Players), "Swap hands with which player? ←/→ and Enter") {
+				KeyCode::Left => self.selected = self.selected.saturating_sub(1),
+				KeyCode::Right => self.selected = (self.selected + 1).min(others.len() - 1),
+				KeyCode::Enter | KeyCode::Char(' ') => return others[self.selected],
+				_ => {},
+			}
+		}
+	}
+
+	/// Asks for a line of text, such as a file name. Returns None if Esc is pressed
+	fn read_line(&mut self, view: &PlayerView, label: &str) -> Option<String> {
+		let mut text: String = String::new();
+		loop {
+			match self.read_key(view, Some(Picker::Hand), &format!("{}{}_", label, text)) {
+				KeyCode::Enter if !text.is_empty() => return Some(text),
+				KeyCode::Esc => return None,
+				KeyCode::Backspace => {
+					text.pop();
+				},
+				KeyCode::Char(x) => text.push(x),
+				_ => {},
+			}
+		}
+	}
+
+	/// Draws the screen and waits for a key. Scrolling the log and quitting are handled here
+	fn read_key(&mut self, view: &PlayerView, picker: Option<Picker>, prompt: &str) -> KeyCode {
+		loop {
+			if let Err(err) = self.draw(view, picker, prompt) {
+				self.quit(&format!("Could not draw the screen: {}", err));
+			}
+			let event: Event = match event::read() {
+				Ok(x) => x,
+				Err(err) => self.quit(&format!("Could not read the keyboard: {}", err)),
+			};
+			let key = match event {
+				Event::Key(key) if key.kind != KeyEventKind::Release => key,
+				_ => continue,
+			};
+			match key.code {
+				KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit("Quit"),
+				KeyCode::Char('q') if prompt == HELP => self.quit("Quit"),
+				KeyCode::PageUp => self.scroll = (self.scroll + 5).min(self.log.len().saturating_sub(1)),
+				KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(5),
+				code => return code,
+			}
+		}
+	}
+
+	/// Puts the terminal back and ends the program
+	fn quit(&mut self, message: &str) -> ! {
+		self.stop();
+		println!("{}", message);
+		::std::process::exit(0);
+	}
+
+	/// Draws the whole screen. `picker` is None while the computer is taking its turn
+	fn draw(&mut self, view: &PlayerView, picker: Option<Picker>, prompt: &str) -> io::Result<()> {
+		let (width, height) = terminal::size()?;
+		let (width, height): (usize, usize) = (width as usize, height as usize);
+		let out: &mut Stdout = &mut self.stdout;
+		queue!(out, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+
+		// Players, with the turn order between them
+		queue!(out, style::SetAttribute(Attribute::Bold), Print(format!(" UNO  Player {}'s turn", seat_label(view.current_player))), style::SetAttribute(Attribute::Reset))?;
+		let arrow: &str = match view.turn_order {
+			TurnOrder::Normal => " → ",
+			TurnOrder::Reverse => " ← ",
+		};
+		queue!(out, cursor::MoveTo(1, 2))?;
+		for (player, size) in view.hand_sizes.iter().enumerate() {
+			if player > 0 {
+				queue!(out, Print(arrow))?;
+			}
+			let you: &str = if player == view.player { " (you)" } else { "" };
+			let uno: &str = if *size == 1 { " UNO!" } else { "" };
+			let text: String = format!("Player {}{}: {} cards{}", seat_label(player), you, size, uno);
+			if player == view.current_player {
+				queue!(out, style::SetAttribute(Attribute::Reverse), Print(text), style::SetAttribute(Attribute::Reset))?;
+			} else {
+				queue!(out, Print(text))?;
+			}
+		}
+
+		// The discard pile, newest first
+		queue!(out, cursor::MoveTo(1, 4), Print(format!("Discard pile ({})  Draw pile ({})  Color: ", view.discard_pile.len(), view.draw_pile_count)))?;
+		queue!(out, style::SetForegroundColor(terminal_color(view.active_color)), Print(view.active_color), style::ResetColor)?;
+		queue!(out, cursor::MoveTo(1, 5))?;
+		for (i, card) in view.discard_pile.iter().rev().take(6).enumerate() {
+			print_card(out, card, if i == 0 { Attribute::Bold } else { Attribute::Dim })?;
+			queue!(out, Print(" "))?;
+		}
+
+		// The event log, newest last
+		let log_top: usize = 7;
+		let log_height: usize = height.saturating_sub(log_top + 6);
+		let end: usize = self.log.len() - self.scroll.min(self.log.len());
+		let start: usize = end.saturating_sub(log_height);
+		for (row, line) in self.log[start..end].iter().enumerate() {
+			let line: String = line.chars().take(width.saturating_sub(2)).collect();
+			queue!(out, cursor::MoveTo(1, (log_top + row) as u16), Print(line))?;
+		}
+		if self.scroll > 0 {
+			queue!(out, cursor::MoveTo(1, (log_top + log_height) as u16), Print(format!("(Scrolled back {} lines)", self.scroll)))?;
+		}
+
+		// The hand, or whatever is being picked
+		let row: u16 = height.saturating_sub(5) as u16;
+		let title: String = match (picker, view.pending) {
+			_ if self.hidden => "Hand hidden".to_owned(),
+			(None, _) => format!("Your hand (Player {}). Waiting for player {}", seat_label(view.player), seat_label(view.current_player)),
+			(_, Some(Pending::DrawnCard(_))) => "You drew the selected card. Play it or pass".to_owned(),
+			(_, Some(Pending::DrawPenalty(n))) => format!("Stack a draw card or draw {}", n),
+			_ => format!("Your hand (Player {})", seat_label(view.player)),
+		};
+		queue!(out, cursor::MoveTo(1, row), Print(title), cursor::MoveTo(1, row + 2))?;
+		match picker {
+			Some(Picker::Colors) => for (i, color) in WILD_COLORS.iter().enumerate() {
+				let attribute: Attribute = if i == self.selected { Attribute::Reverse } else { Attribute::Reset };
+				queue!(out, style::SetForegroundColor(terminal_color(*color)), style::SetAttribute(attribute),
+					Print(format!(" {} ", color)), style::SetAttribute(Attribute::Reset), style::ResetColor, Print(" "))?;
+			},
+			Some(Picker::Players) => {
+				let others = (0..view.num_players()).filter(|x| *x != view.player);
+				for (i, player) in others.enumerate() {
+					let attribute: Attribute = if i == self.selected { Attribute::Reverse } else { Attribute::Reset };
+					queue!(out, style::SetAttribute(attribute), Print(format!(" Player {} ({} cards) ", seat_label(player), view.hand_sizes[player])),
+						style::SetAttribute(Attribute::Reset), Print(" "))?;
+				}
+			},
+			_ if self.hidden => {},
+			_ => {
+				let widths: Vec<usize> = view.hand.iter().map(|card| card_label(card).chars().count() + 3).collect();
+				let (first, last) = visible_cards(&widths, self.selected, width.saturating_sub(6));
+				if first > 0 {
+					queue!(out, Print("< "))?;
+				}
+				for (i, card) in view.hand.iter().enumerate().take(last).skip(first) {
+					let attribute: Attribute = if picker.is_some() && i == self.selected {
+						Attribute::Reverse
+					} else if playable_card(card, &view.top_card) {
+						Attribute::Bold
+					} else {
+						Attribute::Dim
+					};
+					print_card(out, card, attribute)?;
+					queue!(out, Print(" "))?;
+				}
+				if last < view.hand.len() {
+					queue!(out, Print(">"))?;
+				}
+			},
+		}
+		queue!(out, cursor::MoveTo(1, height.saturating_sub(1) as u16), Print(prompt.chars().take(width.saturating_sub(2)).collect::<String>()))?;
+		out.flush()
+	}
+}
+
+impl Drop for Screen {
+	fn drop(&mut self) {
+		self.stop();
+	}
+}
+
+/// A person playing with the full-screen interface. Players at the same terminal share a Screen
+pub struct TuiPlayer {
+	screen: Rc<RefCell<Screen>>,
+}
+
+impl TuiPlayer {
+	pub fn new(screen: Rc<RefCell<Screen>>) -> TuiPlayer {
+		TuiPlayer {
+			screen: screen,
+		}
+	}
+}
+
+impl Player for TuiPlayer {
+	fn choose_action(&mut self, view: &PlayerView) -> Action {
+		loop {
+			match self.choose_command(view) {
+				Command::Act(action) => return action,
+				_ => self.screen.borrow_mut().say("Only actions can be taken right now"),
+			}
+		}
+	}
+
+	fn choose_color(&mut self, view: &PlayerView) -> Color {
+		self.screen.borrow_mut().read_color(view)
+	}
+
+	fn choose_command(&mut self, view: &PlayerView) -> Command {
+		self.screen.borrow_mut().read_command(view)
+	}
+}
+
+/// Returns the command a key gives on the viewer's turn, with the card at `selected` selected
+pub fn command_for_key(key: KeyCode, view: &PlayerView, selected: usize) -> Option<Command> {
+	let action: Action = match key {
+		KeyCode::Enter | KeyCode::Char(' ') => match view.hand.get(selected) {
+			Some(card) => Action::Play(*card, selected),
+			None => return None,
+		},
+		KeyCode::Char('d') => Action::Draw,
+		KeyCode::Char('p') => match view.pending {
+			Some(Pending::DrawnCard(_)) => Action::Pass,
+			_ => Action::Draw,
+		},
+		KeyCode::Char('u') => Action::CallUno,
+		KeyCode::Char('c') => match view.uno_vulnerable {
+			Some(player) if player != view.player => Action::Catch(view.player, player),
+			_ => return None,
+		},
+		KeyCode::Char('z') => return Some(Command::Undo),
+		KeyCode::Char('y') => return Some(Command::Redo),
+		_ => return None,
+	};
+	Some(Command::Act(action))
+}
+
+/// Returns the short text shown on a card, such as `7`, `Skip` or `+4`
+pub fn card_label(card: &Card) -> String {
+	match card.card_type {
+		CardType::Number(x) => x.to_string(),
+		CardType::Skip => "Skip".to_owned(),
+		CardType::Reverse => "Rev".to_owned(),
+		CardType::Plus2 => "+2".to_owned(),
+		CardType::Wild(_) => "Wild".to_owned(),
+		CardType::WildPlus4(_) => "+4".to_owned(),
+	}
+}
+
+/// Returns the first and one past the last of the cards that fit in `width` columns,
+/// keeping the selected card in view. `widths` is how many columns each card takes
+pub fn visible_cards(widths: &[usize], selected: usize, width: usize) -> (usize, usize) {
+	let mut first: usize = selected.min(widths.len());
+	let mut last: usize = first;
+	let mut used: usize = 0;
+	// Fill to the right of the selected card first, then to the left
+	while last < widths.len() && used + widths[last] <= width {
+		used += widths[last];
+		last += 1;
+	}
+	while first > 0 && used + widths[first - 1] <= width {
+		first -= 1;
+		used += widths[first];
+	}
+	(first, last)
+}
+
+fn print_card(out: &mut Stdout, card: &Card, attribute: Attribute) -> io::Result<()> {
+	let color: Color = match card.card_type {
+		CardType::Wild(x) | CardType::WildPlus4(x) => x,
+		_ => card.color,
+	};
+	queue!(out, style::SetBackgroundColor(terminal_color(color)), style::SetForegroundColor(style::Color::Black),
+		style::SetAttribute(attribute), Print(format!(" {} ", card_label(card))), style::SetAttribute(Attribute::Reset), style::ResetColor)
+}
+
+fn terminal_color(color: Color) -> style::Color {
+	match color {
+		Color::Red => style::Color::Red,
+		Color::Green => style::Color::Green,
+		Color::Blue => style::Color::Blue,
+		Color::Yellow => style::Color::Yellow,
+		Color::Any => style::Color::White,
+	}
+}